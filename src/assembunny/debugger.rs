@@ -0,0 +1,295 @@
+use std::{collections::BTreeSet, fmt::Write as _, io::{self, BufRead, Write}};
+
+use crate::{assembunny::{Instruction, Machine, REGISTERS, Register, register, register_name}, utils::parser::{Parser, identifier, integer, parse_all, whitespace}};
+
+/// A debugger command. Lines are numbered from 1, like in the puzzle input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    Break(usize),
+    Delete(usize),
+    Step(usize),
+    Continue,
+    Registers,
+    Set(Register, i64),
+    Watch(Register),
+    Unwatch(Register),
+    List,
+    Help,
+    Quit,
+}
+
+const HELP: &str = "\
+break|b <line>      stop before executing <line>
+delete|d <line>     remove the breakpoint at <line>
+step|s [n]          execute n instructions, 1 by default
+continue|c          run until a breakpoint, the end of the program or the step limit
+registers|r         print the registers
+set <reg> <value>   change a register
+watch|w <reg>       report every change to a register
+unwatch <reg>       stop reporting changes to a register
+list|l              print the program, including toggled instructions
+help|h              print this help
+quit|q              leave the debugger
+";
+
+/// Matches the whole word `long` or its abbreviation `short`.
+fn keyword<'a>(long: &'static str, short: &'static str) -> impl Parser<'a, &'a str> {
+    identifier().try_map(move |word: &str| {
+        if word == long || word == short { Ok(word) } else { Err(format!("Expected \"{long}\"")) }
+    })
+}
+
+fn argument<'a, T>(name: &'static str, short: &'static str, value: impl Parser<'a, T>) -> impl Parser<'a, T> {
+    keyword(name, short).then_ignore(whitespace()).ignore_then(value)
+}
+
+pub fn command<'a>() -> impl Parser<'a, Command> {
+    let set = argument("set", "set", register()).then_ignore(whitespace()).then(integer())
+        .map(|(register, value)| Command::Set(register, value));
+    let step = argument("step", "s", integer()).map(Command::Step)
+        .or(keyword("step", "s").map(|_| Command::Step(1)));
+    argument("break", "b", integer()).map(Command::Break)
+        .or(argument("delete", "d", integer()).map(Command::Delete))
+        .or(step)
+        .or(keyword("continue", "c").map(|_| Command::Continue))
+        .or(keyword("registers", "r").map(|_| Command::Registers))
+        .or(set)
+        .or(argument("watch", "w", register()).map(Command::Watch))
+        .or(argument("unwatch", "unwatch", register()).map(Command::Unwatch))
+        .or(keyword("list", "l").map(|_| Command::List))
+        .or(keyword("help", "h").map(|_| Command::Help))
+        .or(keyword("quit", "q").map(|_| Command::Quit))
+}
+
+/// Interactive debugger around an assembunny machine.
+pub struct Debugger {
+    machine: Machine,
+    /// The program before any toggles, to point out toggled instructions
+    original: Vec<Instruction>,
+    /// Program counters to stop at
+    breakpoints: BTreeSet<usize>,
+    watches: BTreeSet<Register>,
+    /// Most instructions a single `continue` runs, since many programs never halt
+    step_limit: usize,
+}
+
+impl Debugger {
+    /// Fused loops would run past breakpoints and watched changes inside them,
+    /// so the machine is always debugged one instruction at a time.
+    pub fn new(machine: Machine) -> Self {
+        let machine = machine.unoptimized();
+        let original = machine.program().to_vec();
+        Self { machine, original, breakpoints: BTreeSet::new(), watches: BTreeSet::new(), step_limit: 10_000_000 }
+    }
+
+    /// Makes `continue` give control back after `step_limit` instructions.
+    pub fn with_step_limit(mut self, step_limit: usize) -> Self {
+        self.step_limit = step_limit.max(1);
+        self
+    }
+
+    pub fn machine(&self) -> &Machine {
+        &self.machine
+    }
+
+    /// Runs a single command and returns what it prints.
+    pub fn execute(&mut self, command: Command) -> String {
+        let mut output = String::new();
+        match command {
+            Command::Break(0) | Command::Delete(0) => {
+                writeln!(output, "Lines start at 1").unwrap();
+            },
+            Command::Break(line) => {
+                self.breakpoints.insert(line - 1);
+                writeln!(output, "Breakpoint at line {line}").unwrap();
+            },
+            Command::Delete(line) => {
+                if !self.breakpoints.remove(&(line - 1)) {
+                    writeln!(output, "No breakpoint at line {line}").unwrap();
+                }
+            },
+            Command::Step(count) => {
+                for _ in 0..count {
+                    if self.machine.is_halted() { break; }
+                    self.step(&mut output);
+                }
+                self.write_position(&mut output);
+            },
+            Command::Continue => {
+                // Leave the breakpoint the machine is currently stopped at
+                if !self.machine.is_halted() {
+                    self.step(&mut output);
+                }
+                let mut steps = 1;
+                while !self.machine.is_halted() && !self.breakpoints.contains(&self.machine.pc()) && steps < self.step_limit {
+                    self.step(&mut output);
+                    steps += 1;
+                }
+                if !self.machine.is_halted() {
+                    if self.breakpoints.contains(&self.machine.pc()) {
+                        writeln!(output, "Breakpoint at line {}", self.machine.pc() + 1).unwrap();
+                    } else {
+                        writeln!(output, "Stopped after {steps} steps").unwrap();
+                    }
+                }
+                self.write_position(&mut output);
+            },
+            Command::Registers => {
+                let registers = self.machine.registers();
+                let values: Vec<_> = (0..REGISTERS)
+                    .map(|r| format!("{}={}", register_name(r), registers[r]))
+                    .collect();
+                writeln!(output, "{}", values.join(" ")).unwrap();
+            },
+            Command::Set(register, value) => {
+                self.machine.set_register(register_name(register), value);
+            },
+            Command::Watch(register) => {
+                self.watches.insert(register);
+            },
+            Command::Unwatch(register) => {
+                self.watches.remove(&register);
+            },
+            Command::List => {
+                for (pc, instruction) in self.machine.program().iter().enumerate() {
+                    let current = if pc == self.machine.pc() { '>' } else { ' ' };
+                    let breakpoint = if self.breakpoints.contains(&pc) { '*' } else { ' ' };
+                    let toggled = if self.original[pc] != *instruction { "  (toggled)" } else { "" };
+                    writeln!(output, "{current}{breakpoint}{:>3}: {instruction}{toggled}", pc + 1).unwrap();
+                }
+            },
+            Command::Help => output.push_str(HELP),
+            Command::Quit => {},
+        }
+        output
+    }
+
+    /// Executes one step, reporting changes to watched registers.
+    fn step(&mut self, output: &mut String) {
+        let line = self.machine.pc() + 1;
+        let before = self.machine.registers();
+        self.machine.step();
+        if let Some(value) = self.machine.output() {
+            writeln!(output, "Output {value}").unwrap();
+        }
+        let after = self.machine.registers();
+        for register in &self.watches {
+            if before[*register] != after[*register] {
+                writeln!(output, "{}: {} -> {} (line {line})", register_name(*register), before[*register], after[*register]).unwrap();
+            }
+        }
+    }
+
+    fn write_position(&self, output: &mut String) {
+        match self.machine.instruction(self.machine.pc()) {
+            Some(instruction) => writeln!(output, "{:>3}: {instruction}", self.machine.pc() + 1).unwrap(),
+            None => writeln!(output, "Halted").unwrap(),
+        }
+    }
+
+    /// Reads commands from `input` until it ends or `quit` is entered, writing the results to `output`.
+    pub fn repl(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        write!(output, "(bunny) ")?;
+        output.flush()?;
+        for line in input.lines() {
+            let line = line?;
+            match parse_all(&command(), line.trim()) {
+                Ok(Command::Quit) => break,
+                Ok(command) => write!(output, "{}", self.execute(command))?,
+                Err(err) => writeln!(output, "{err}, try \"help\"")?,
+            }
+            write!(output, "(bunny) ")?;
+            output.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::{assembunny::{Machine, debugger::{Command, Debugger, command}, instruction}, utils::parser::{parse_all, parse_lines}};
+
+    fn debugger() -> Debugger {
+        let program = parse_lines(&instruction(), "cpy 2 b\ninc a\ndec b\njnz b -2\ntgl a\ninc c").unwrap();
+        Debugger::new(Machine::new(program))
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(Ok(Command::Break(4)), parse_all(&command(), "b 4"));
+        assert_eq!(Ok(Command::Step(1)), parse_all(&command(), "step"));
+        assert_eq!(Ok(Command::Step(10)), parse_all(&command(), "s 10"));
+        assert_eq!(Ok(Command::Set(0, -3)), parse_all(&command(), "set a -3"));
+        assert_eq!(Ok(Command::Watch(3)), parse_all(&command(), "watch d"));
+        assert!(parse_all(&command(), "stepp").is_err());
+        assert!(debugger().execute(Command::Help).contains("help|h"));
+        assert!(parse_all(&command(), "watch e").is_err());
+    }
+
+    #[test]
+    fn test_breakpoints_and_watches() {
+        let mut debugger = debugger();
+        debugger.execute(Command::Break(4));
+        debugger.execute(Command::Watch(1));
+        assert_eq!("b: 0 -> 2 (line 1)\nb: 2 -> 1 (line 3)\nBreakpoint at line 4\n  4: jnz b -2\n", debugger.execute(Command::Continue));
+        assert_eq!("a=1 b=1 c=0 d=0\n", debugger.execute(Command::Registers));
+        assert_eq!("  2: inc a\n", debugger.execute(Command::Step(1)));
+        debugger.execute(Command::Delete(4));
+        debugger.execute(Command::Unwatch(1));
+        assert_eq!("Halted\n", debugger.execute(Command::Continue));
+        assert_eq!(2, debugger.machine().register('a'));
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut debugger = debugger();
+        assert_eq!("Lines start at 1\n", debugger.execute(Command::Break(0)));
+        assert_eq!("Lines start at 1\n", debugger.execute(Command::Delete(0)));
+
+        // A program that never halts gives control back after the step limit
+        let program = parse_lines(&instruction(), "inc a\njnz 1 -1").unwrap();
+        let mut debugger = Debugger::new(Machine::new(program)).with_step_limit(100);
+        assert_eq!("Stopped after 100 steps\n  1: inc a\n", debugger.execute(Command::Continue));
+        assert_eq!(50, debugger.machine().register('a'));
+    }
+
+    #[test]
+    fn test_optimized_machine() {
+        let program = parse_lines(&instruction(), "cpy 2 b\ninc a\ndec b\njnz b -2\ntgl a\ninc c").unwrap();
+        let mut debugger = Debugger::new(Machine::new(program).optimized());
+        // Both stop inside the add loop instead of skipping it
+        debugger.execute(Command::Break(3));
+        debugger.execute(Command::Watch(0));
+        assert_eq!("a: 0 -> 1 (line 2)\nBreakpoint at line 3\n  3: dec b\n", debugger.execute(Command::Continue));
+    }
+
+    #[test]
+    fn test_list() {
+        let mut debugger = debugger();
+        // The add loop leaves a at 1, so the toggle hits the line after it
+        debugger.execute(Command::Set(0, -1));
+        debugger.execute(Command::Break(2));
+        debugger.execute(Command::Step(8));
+        let listing = debugger.execute(Command::List);
+        assert_eq!(vec![
+            "    1: cpy 2 b",
+            " *  2: inc a",
+            "    3: dec b",
+            "    4: jnz b -2",
+            "    5: tgl a",
+            ">   6: dec c  (toggled)",
+        ], listing.lines().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_repl() {
+        let mut output = Vec::new();
+        debugger().repl(Cursor::new("s 2\nr\nfoo\nq\nr\n"), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("  3: dec b\n(bunny) a=1 b=2 c=0 d=0\n"));
+        assert!(output.contains("try \"help\""));
+        assert_eq!(4, output.matches("(bunny)").count());
+    }
+}
@@ -0,0 +1,509 @@
+use std::fmt::Display;
+
+use crate::utils::parser::{Parser, integer, literal, one_char, whitespace};
+
+pub mod debugger;
+
+/// Index of a register in the register file.
+pub type Register = usize;
+
+/// Number of registers, named `a` to `d`.
+pub const REGISTERS: usize = 4;
+
+/// The register called `name`, from `a` to `d`.
+pub fn register_index(name: char) -> Register {
+    assert!(('a'..='d').contains(&name), "Unknown register {name}");
+    name as usize - 'a' as usize
+}
+
+/// The name of register `register`, from `a` to `d`.
+pub fn register_name(register: Register) -> char {
+    (b'a' + register as u8) as char
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Value {
+    Register(Register),
+    Constant(i64)
+}
+
+fn register<'a>() -> impl Parser<'a, Register> {
+    one_char(|c| ('a'..='d').contains(&c)).map(register_index)
+}
+
+fn value<'a>() -> impl Parser<'a, Value> {
+    integer().map(Value::Constant).or(register().map(Value::Register))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    Cpy{ src: Value, dst: Value },
+    Inc(Value),
+    Dec(Value),
+    Jnz{ test: Value, offset: Value },
+    Tgl(Register),
+    Out(Value),
+}
+
+impl Instruction {
+    /// The instruction that `tgl` turns this one into.
+    pub fn toggled(&self) -> Instruction {
+        match *self {
+            Instruction::Inc(r) => Instruction::Dec(r),
+            Instruction::Dec(r) => Instruction::Inc(r),
+            Instruction::Tgl(r) => Instruction::Inc(Value::Register(r)),
+            Instruction::Out(value) => Instruction::Inc(value),
+            Instruction::Cpy { src, dst } => Instruction::Jnz { test: src, offset: dst },
+            Instruction::Jnz { test, offset } => Instruction::Cpy { src: test, dst: offset },
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Register(r) => write!(f, "{}", register_name(*r)),
+            Value::Constant(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Cpy { src, dst } => write!(f, "cpy {src} {dst}"),
+            Instruction::Inc(value) => write!(f, "inc {value}"),
+            Instruction::Dec(value) => write!(f, "dec {value}"),
+            Instruction::Jnz { test, offset } => write!(f, "jnz {test} {offset}"),
+            Instruction::Tgl(r) => write!(f, "tgl {}", register_name(*r)),
+            Instruction::Out(value) => write!(f, "out {value}"),
+        }
+    }
+}
+
+pub fn instruction<'a>() -> impl Parser<'a, Instruction> {
+    let cpy = literal("cpy ").ignore_then(value()).then_ignore(whitespace()).then(value())
+        .map(|(src, dst)| Instruction::Cpy { src, dst });
+    let jnz = literal("jnz ").ignore_then(value()).then_ignore(whitespace()).then(value())
+        .map(|(test, offset)| Instruction::Jnz { test, offset });
+    let inc = literal("inc ").ignore_then(register()).map(|r| Instruction::Inc(Value::Register(r)));
+    let dec = literal("dec ").ignore_then(register()).map(|r| Instruction::Dec(Value::Register(r)));
+    let tgl = literal("tgl ").ignore_then(register()).map(Instruction::Tgl);
+    let out = literal("out ").ignore_then(value()).map(Instruction::Out);
+    cpy.or(jnz).or(inc).or(dec).or(tgl).or(out)
+}
+
+/// A pseudo-instruction that replaces a whole loop found by `find_idioms`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fused {
+    /// `inc dst`, `dec counter`, `jnz counter -2` (in either order): adds `counter` to `dst` and clears it
+    Add { dst: Register, counter: Register },
+    /// `cpy src inner`, an add loop of `inner` into `dst`, `dec outer`, `jnz outer -5`:
+    /// adds `src * outer` to `dst` and clears `inner` and `outer`
+    Mul { dst: Register, src: Value, inner: Register, outer: Register },
+}
+
+impl Fused {
+    /// Number of instructions the pseudo-instruction replaces.
+    pub fn span(&self) -> usize {
+        match self {
+            Fused::Add { .. } => 3,
+            Fused::Mul { .. } => 6,
+        }
+    }
+}
+
+fn add_idiom(instructions: &[Instruction]) -> Option<(Register, Register)> {
+    let (dst, counter) = match instructions {
+        [Instruction::Inc(Value::Register(dst)), Instruction::Dec(Value::Register(counter)), ..] => (*dst, *counter),
+        [Instruction::Dec(Value::Register(counter)), Instruction::Inc(Value::Register(dst)), ..] => (*dst, *counter),
+        _ => return None,
+    };
+    let jump = Instruction::Jnz { test: Value::Register(counter), offset: Value::Constant(-2) };
+    (dst != counter && instructions.get(2) == Some(&jump)).then_some((dst, counter))
+}
+
+fn mul_idiom(instructions: &[Instruction]) -> Option<Fused> {
+    let [Instruction::Cpy { src, dst: Value::Register(inner) }, _, _, _, Instruction::Dec(Value::Register(outer)), jump, ..] = instructions else {
+        return None;
+    };
+    let (dst, counter) = add_idiom(&instructions[1..])?;
+    let outer_jump = Instruction::Jnz { test: Value::Register(*outer), offset: Value::Constant(-5) };
+    let distinct = counter == *inner && dst != *outer && inner != outer;
+    let src_unchanged = ![dst, *inner, *outer].iter().any(|r| *src == Value::Register(*r));
+    (distinct && src_unchanged && *jump == outer_jump).then_some(Fused::Mul { dst, src: *src, inner: *inner, outer: *outer })
+}
+
+/// Finds every add and multiply loop in the program, at the position of their first instruction.
+pub fn find_idioms(instructions: &[Instruction]) -> Vec<Option<Fused>> {
+    (0..instructions.len()).map(|pc| {
+        let rest = &instructions[pc..];
+        mul_idiom(rest).or_else(|| add_idiom(rest).map(|(dst, counter)| Fused::Add { dst, counter }))
+    }).collect()
+}
+
+/// Why a run over the output of a machine ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    Halted,
+    /// The output callback asked to stop
+    Rejected,
+    /// The step budget ran out
+    OutOfSteps,
+}
+
+/// Everything that decides how a machine continues, so equal snapshots mean the machine is in a loop.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Snapshot {
+    pc: usize,
+    registers: [i64; REGISTERS],
+    program: Vec<Instruction>,
+}
+
+/// An assembunny computer running a program, which may rewrite itself through `tgl`.
+#[derive(Clone, Debug)]
+pub struct Machine {
+    /// The program as changed by any toggles so far
+    program: Vec<Instruction>,
+    registers: [i64; REGISTERS],
+    pc: usize,
+    /// The value sent by `out` during the last step
+    output: Option<i64>,
+    /// Loops that are executed as a single step, when optimization is enabled
+    fused: Option<Vec<Option<Fused>>>,
+}
+
+impl Machine {
+    pub fn new(program: Vec<Instruction>) -> Self {
+        Self { program, registers: [0; REGISTERS], pc: 0, output: None, fused: None }
+    }
+
+    /// Enables the peephole optimizer, which runs add and multiply loops in a single step.
+    /// The loops are found again whenever `tgl` changes the program.
+    pub fn optimized(mut self) -> Self {
+        self.fused = Some(Vec::new());
+        self.find_idioms();
+        self
+    }
+
+    /// Turns the peephole optimizer off again, so every instruction runs on its own.
+    pub fn unoptimized(mut self) -> Self {
+        self.fused = None;
+        self
+    }
+
+    fn find_idioms(&mut self) {
+        if let Some(fused) = &mut self.fused {
+            *fused = find_idioms(&self.program);
+        }
+    }
+
+    /// The pseudo-instruction that will run at `pc`, if any.
+    pub fn fused(&self, pc: usize) -> Option<Fused> {
+        *self.fused.as_ref()?.get(pc)?
+    }
+
+    /// Runs a fused loop when its registers allow it. Loops that would count down from zero or below
+    /// run for a very long time, so those are left to the normal instructions.
+    fn run_fused(&mut self, fused: Fused) -> bool {
+        match fused {
+            Fused::Add { dst, counter } => {
+                let count = self.registers[counter];
+                if count <= 0 { return false; }
+                self.registers[dst] += count;
+                self.registers[counter] = 0;
+            },
+            Fused::Mul { dst, src, inner, outer } => {
+                let (factor, count) = (self.read(src), self.registers[outer]);
+                if factor <= 0 || count <= 0 { return false; }
+                self.registers[dst] += factor * count;
+                self.registers[inner] = 0;
+                self.registers[outer] = 0;
+            },
+        }
+        self.pc += fused.span();
+        true
+    }
+
+    pub fn register(&self, name: char) -> i64 {
+        self.registers[register_index(name)]
+    }
+
+    pub fn registers(&self) -> [i64; REGISTERS] {
+        self.registers
+    }
+
+    pub fn set_register(&mut self, name: char, value: i64) {
+        self.registers[register_index(name)] = value;
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn set_pc(&mut self, pc: usize) {
+        self.pc = pc;
+    }
+
+    /// The whole program, as changed by any toggles.
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    /// The instruction at `pc`, as changed by any toggles.
+    pub fn instruction(&self, pc: usize) -> Option<&Instruction> {
+        self.program.get(pc)
+    }
+
+    /// The machine halts when the program counter leaves the program.
+    pub fn is_halted(&self) -> bool {
+        self.pc >= self.program.len()
+    }
+
+    fn read(&self, value: Value) -> i64 {
+        match value {
+            Value::Register(r) => self.registers[r],
+            Value::Constant(v) => v,
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot { pc: self.pc, registers: self.registers, program: self.program.clone() }
+    }
+
+    /// The value sent by the instruction executed in the last step, if it was an `out`.
+    pub fn output(&self) -> Option<i64> {
+        self.output
+    }
+
+    /// Executes a single instruction. Returns false when the machine has halted.
+    pub fn step(&mut self) -> bool {
+        self.step_before(&[])
+    }
+
+    /// Like `step`, but a fused loop with one of the `breakpoints` inside it is run one instruction
+    /// at a time, so the breakpoint isn't jumped over.
+    fn step_before(&mut self, breakpoints: &[usize]) -> bool {
+        self.output = None;
+        if let Some(fused) = self.fused(self.pc)
+            && !breakpoints.iter().any(|pc| (self.pc + 1..self.pc + fused.span()).contains(pc))
+            && self.run_fused(fused)
+        {
+            return !self.is_halted();
+        }
+        let Some(instruction) = self.program.get(self.pc).copied() else { return false };
+        self.execute(instruction);
+        !self.is_halted()
+    }
+
+    fn execute(&mut self, instruction: Instruction) {
+        match instruction {
+            Instruction::Cpy { src, dst } => {
+                // Toggling can produce a copy into a constant, which is skipped
+                if let Value::Register(dst) = dst {
+                    self.registers[dst] = self.read(src);
+                }
+            },
+            // Toggling can also produce an increment or decrement of a constant, which is skipped too
+            Instruction::Inc(value) => {
+                if let Value::Register(reg) = value {
+                    self.registers[reg] += 1;
+                }
+            },
+            Instruction::Dec(value) => {
+                if let Value::Register(reg) = value {
+                    self.registers[reg] -= 1;
+                }
+            },
+            Instruction::Jnz { test, offset } => {
+                if self.read(test) != 0 {
+                    self.pc = self.pc.checked_add_signed(self.read(offset) as isize).unwrap_or(usize::MAX);
+                    return;
+                }
+            },
+            Instruction::Tgl(offset) => {
+                if let Some(target) = self.pc.checked_add_signed(self.registers[offset] as isize)
+                    && let Some(instruction) = self.program.get_mut(target)
+                {
+                    *instruction = instruction.toggled();
+                    self.find_idioms();
+                }
+            },
+            Instruction::Out(value) => {
+                self.output = Some(self.read(value));
+            },
+        }
+        self.pc += 1;
+    }
+
+    /// Runs until the machine halts.
+    pub fn run(&mut self) {
+        if self.fused.is_some() {
+            while self.step() {}
+        } else {
+            // Nothing to check between instructions, so they can be executed back to back
+            self.output = None;
+            while let Some(instruction) = self.program.get(self.pc).copied() {
+                self.output = None;
+                self.execute(instruction);
+            }
+        }
+    }
+
+    /// Runs until the machine halts or is about to execute the instruction at one of the `breakpoints`.
+    /// Returns true when a breakpoint was hit.
+    pub fn run_until(&mut self, breakpoints: &[usize]) -> bool {
+        while !self.is_halted() {
+            if breakpoints.contains(&self.pc) {
+                return true;
+            }
+            self.step_before(breakpoints);
+        }
+        false
+    }
+
+    /// Runs for at most `max_steps` instructions, passing every value sent by `out` to `on_output`
+    /// until it returns false.
+    pub fn run_with_output(&mut self, max_steps: usize, mut on_output: impl FnMut(i64) -> bool) -> Stop {
+        for _ in 0..max_steps {
+            let running = self.step();
+            if let Some(value) = self.output && !on_output(value) {
+                return Stop::Rejected;
+            }
+            if !running {
+                return Stop::Halted;
+            }
+        }
+        if self.is_halted() { Stop::Halted } else { Stop::OutOfSteps }
+    }
+
+    /// Runs until `count` values have been sent, the machine halts or `max_steps` instructions have run.
+    pub fn run_outputs(&mut self, count: usize, max_steps: usize) -> Vec<i64> {
+        let mut outputs = Vec::with_capacity(count);
+        if count > 0 {
+            self.run_with_output(max_steps, |value| {
+                outputs.push(value);
+                outputs.len() < count
+            });
+        }
+        outputs
+    }
+
+    /// Every value sent by the machine, running it as far as needed. Ends when the machine halts.
+    pub fn outputs(&mut self) -> impl Iterator<Item=i64> + '_ {
+        std::iter::from_fn(move || {
+            while self.step() {
+                if self.output.is_some() {
+                    return self.output;
+                }
+            }
+            self.output
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{assembunny::{Fused, Instruction, Machine, Stop, Value, instruction}, utils::parser::parse_lines};
+
+    #[test]
+    fn test_toggle() {
+        let program = parse_lines(&instruction(), "cpy 2 a\ntgl a\ntgl a\ntgl a\ncpy 1 a\ndec a\ndec a").unwrap();
+        let mut machine = Machine::new(program);
+        machine.run();
+        assert_eq!(3, machine.register('a'));
+        assert_eq!(Some(&Instruction::Jnz { test: Value::Constant(1), offset: Value::Register(0) }), machine.instruction(4));
+        assert_eq!("jnz 1 a", machine.instruction(4).unwrap().to_string());
+
+        // Toggling `out 5` twice gives `dec 5`, which is skipped like any other invalid instruction
+        let program = parse_lines(&instruction(), "cpy 3 a\ntgl a\ncpy 1 a\ntgl a\nout 5\ninc b\ninc b").unwrap();
+        let mut machine = Machine::new(program);
+        machine.run();
+        assert_eq!(Some(&Instruction::Dec(Value::Constant(5))), machine.instruction(4));
+        assert_eq!(2, machine.register('b'));
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let program = parse_lines(&instruction(), "cpy 3 b\ninc a\ndec b\njnz b -2").unwrap();
+        let mut machine = Machine::new(program);
+        assert!(machine.run_until(&[3]));
+        assert_eq!((1, 2), (machine.register('a'), machine.register('b')));
+        machine.step();
+        assert_eq!(1, machine.pc());
+        assert!(!machine.run_until(&[]));
+        assert_eq!(3, machine.register('a'));
+        assert!(machine.is_halted());
+
+        // A breakpoint inside a multiply loop stops in the middle of it
+        let program = parse_lines(&instruction(), "cpy 4 b\ncpy 3 d\ncpy b c\ninc a\ndec c\njnz c -2\ndec d\njnz d -5").unwrap();
+        let mut machine = Machine::new(program).optimized();
+        assert_eq!(Some(Fused::Mul { dst: 0, src: Value::Register(1), inner: 2, outer: 3 }), machine.fused(2));
+        assert!(machine.run_until(&[4]));
+        assert_eq!((4, 1, 4), (machine.pc(), machine.register('a'), machine.register('c')));
+        assert!(!machine.run_until(&[]));
+        assert_eq!(12, machine.register('a'));
+    }
+
+    #[test]
+    fn test_output() {
+        // Counts down from a, sending every value
+        let program = parse_lines(&instruction(), "out a\ndec a\njnz a -2\nout 0").unwrap();
+        let mut machine = Machine::new(program);
+        machine.set_register('a', 3);
+        assert_eq!(vec![3, 2, 1, 0], machine.clone().outputs().collect::<Vec<_>>());
+        assert_eq!(vec![3, 2], machine.clone().run_outputs(2, 1000));
+        assert_eq!(vec![3], machine.clone().run_outputs(5, 2));
+
+        let mut sent = Vec::new();
+        let stop = machine.clone().run_with_output(1000, |value| {
+            sent.push(value);
+            value > 2
+        });
+        assert_eq!((Stop::Rejected, vec![3, 2]), (stop, sent));
+        assert_eq!(Stop::OutOfSteps, machine.clone().run_with_output(3, |_| true));
+        assert_eq!(Stop::Halted, machine.run_with_output(1000, |_| true));
+
+        // Like stepping, running only keeps a value sent by the very last instruction
+        let program = parse_lines(&instruction(), "out 1\ninc a").unwrap();
+        let mut machine = Machine::new(program);
+        machine.run();
+        assert_eq!(None, machine.output());
+        let mut machine = Machine::new(parse_lines(&instruction(), "out 1").unwrap());
+        machine.run();
+        assert_eq!(Some(1), machine.output());
+        machine.set_pc(1);
+        machine.run();
+        assert_eq!(None, machine.output());
+    }
+
+    #[test]
+    fn test_optimizer() {
+        let program = parse_lines(&instruction(), r#"cpy 4 b
+cpy 3 d
+cpy b c
+inc a
+dec c
+jnz c -2
+dec d
+jnz d -5
+cpy 4 c
+tgl c
+cpy 5 c
+dec c
+inc a
+jnz c -2"#).unwrap();
+        let mut optimized = Machine::new(program.clone()).optimized();
+        assert_eq!(Some(Fused::Mul { dst: 0, src: Value::Register(1), inner: 2, outer: 3 }), optimized.fused(2));
+        assert_eq!(Some(Fused::Add { dst: 0, counter: 2 }), optimized.fused(3));
+        assert_eq!(Some(Fused::Add { dst: 0, counter: 2 }), optimized.fused(11));
+        optimized.run();
+
+        // The toggle turns the last add loop into a copy, which must no longer be fused
+        let mut plain = Machine::new(program);
+        plain.run();
+        assert_eq!(plain.registers(), optimized.registers());
+        assert_eq!(13, optimized.register('a'));
+        assert_eq!(None, optimized.fused(11));
+    }
+}
@@ -0,0 +1,13 @@
+use std::{env, fs, io, process};
+
+use aoc_rust_2016::{assembunny::{Machine, debugger::Debugger}, day::AocDay, day12::Day12};
+
+/// Steps through an assembunny program: `cargo run --bin assembunny_debug -- input/day23.txt`
+fn main() -> io::Result<()> {
+    let Some(filename) = env::args().nth(1) else {
+        eprintln!("Usage: assembunny_debug <program>");
+        process::exit(1);
+    };
+    let program = Day12::parse(&fs::read_to_string(filename)?);
+    Debugger::new(Machine::new(program)).repl(io::stdin().lock(), io::stdout())
+}
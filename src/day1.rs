@@ -1,6 +1,4 @@
-use std::collections::HashSet;
-
-use crate::{day::AocDay, utils::direction::Direction};
+use crate::{day::AocDay, utils::{direction::Direction, point::{Point, YAxis}, sparse_grid::SparseGrid}};
 
 pub struct Day1;
 
@@ -22,21 +20,20 @@ impl AocDay for Day1 {
     }
 
     fn part1(input: &Vec<(char, i32)>) -> i32 {
-        let (_, position) = input.iter().fold((Direction::North, (0, 0)), |(facing, (x, y)), direction| {
+        let (_, position) = input.iter().fold((Direction::North, Point::ORIGIN), |(facing, position), direction| {
             let new_facing = match direction.0 {
                 'L' => facing.left(),
                 'R' => facing.right(),
                 _ => panic!("Unknown direction {}", direction.0)
             };
-            let offset = new_facing.offset();
-            (new_facing, (x + offset.0 * direction.1, y + offset.1 * direction.1))
+            (new_facing, position + new_facing.to_point(YAxis::Up) * direction.1 as i64)
         });
-        position.0.abs() + position.1.abs()
+        position.manhattan(&Point::ORIGIN) as i32
     }
 
     fn part2(input: &Vec<(char, i32)>) -> i32 {
-        let mut visited: HashSet<(i32, i32)> = HashSet::new();
-        let (mut x, mut y) = (0, 0);
+        let mut visited: SparseGrid<bool> = SparseGrid::empty();
+        let mut position = Point::ORIGIN;
         let mut direction = Direction::North;
         for (facing, amount) in input {
             direction = match facing {
@@ -44,14 +41,11 @@ impl AocDay for Day1 {
                 'R' => direction.right(),
                 _ => panic!("Unknown direction {}", facing)
             };
-            let offset = direction.offset();
             for _ in 0..*amount {
-                x += offset.0;
-                y += offset.1;
-                if visited.contains(&(x, y)) {
-                    return x.abs() + y.abs();
+                position += direction.to_point(YAxis::Up);
+                if visited.set(position.x, position.y, true) {
+                    return position.manhattan(&Point::ORIGIN) as i32;
                 }
-                visited.insert((x, y));
             }
         }
         return -1;
@@ -68,4 +62,9 @@ mod tests {
         assert_eq!(2, Day1::part1(&Day1::parse("R2, R2, R2")));
         assert_eq!(12, Day1::part1(&Day1::parse("R5, L5, R5, R3")));
     }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(4, Day1::part2(&Day1::parse("R8, R4, R4, R8")));
+    }
 }
\ No newline at end of file
@@ -1,10 +1,10 @@
 use core::panic;
-use std::sync::LazyLock;
+use std::{str::FromStr, sync::LazyLock};
 
 use itertools::Itertools;
 use regex::Regex;
 
-use crate::{day::AocDay, utils::regex::capture_get_usize};
+use crate::{day::AocDay, utils::{error::ParseError, regex::LinePattern}};
 
 pub struct Day10;
 
@@ -27,6 +27,21 @@ pub enum Destination {
     Output(usize)
 }
 
+impl FromStr for Destination {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, id) = s.split_once(" ")
+            .ok_or_else(|| ParseError::new(format!("Invalid destination {s}")))?;
+        let id = id.parse().map_err(|_| ParseError::new(format!("Invalid id {id}")))?;
+        match kind {
+            "bot" => Ok(Destination::Bot(id)),
+            "output" => Ok(Destination::Output(id)),
+            _ => Err(ParseError::new(format!("Invalid destination {kind}")))
+        }
+    }
+}
+
 fn find_bot(
     initial_chips: &Vec<Vec<usize>>, 
     bots: &Vec<Bot>, 
@@ -88,24 +103,9 @@ impl AocDay for Day10 {
         let mut value_lines: Vec<[usize; 2]> = Vec::new();
         let mut bot_lines: Vec<(usize, Bot)> = Vec::new();
         for line in contents.lines() {
-            if let Some(capture) = VALUE_PATTERN.captures(line) {
-                let value = capture_get_usize(&capture, 1);
-                let bot = capture_get_usize(&capture, 2);
+            if let Some((value, bot)) = VALUE_PATTERN.parse_captures(line).unwrap() {
                 value_lines.push([value, bot]);
-            } else if let Some(capture) = BOT_PATTERN.captures(line) {
-                let bot = capture_get_usize(&capture, 1);
-                let low_str = capture.get(2).unwrap().as_str().split_once(" ").unwrap();
-                let low = match low_str.0 {
-                    "bot" => Destination::Bot(low_str.1.parse().unwrap()),
-                    "output" => Destination::Output(low_str.1.parse().unwrap()),
-                    _ => panic!("Invalid destination {}", low_str.0)
-                };
-                let high_str = capture.get(3).unwrap().as_str().split_once(" ").unwrap();
-                let high = match high_str.0 {
-                    "bot" => Destination::Bot(high_str.1.parse().unwrap()),
-                    "output" => Destination::Output(high_str.1.parse().unwrap()),
-                    _ => panic!("Invalid destination {}", high_str.0)
-                };
+            } else if let Some((bot, low, high)) = BOT_PATTERN.parse_captures(line).unwrap() {
                 max_bots = max_bots.max(bot);
                 bot_lines.push((bot, Bot { low, high }));
             } else {
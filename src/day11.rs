@@ -1,11 +1,11 @@
-use std::{collections::{HashMap, HashSet, VecDeque}, sync::LazyLock, usize};
+use std::{collections::HashMap, sync::LazyLock, usize};
 
 use std::fmt::Debug;
 use indicatif::{ProgressBar, ProgressStyle};
 use itertools::Itertools;
 use regex::Regex;
 
-use crate::day::AocDay;
+use crate::{day::AocDay, utils::{regex::CaptureGroups, search::dijkstra_cost}};
 
 pub struct Day11;
 
@@ -121,15 +121,9 @@ impl AocDay for Day11 {
 
         let floors: [Vec<_>; 4] = contents.lines().map(|line| {
             let chips = CHIP_PATTERN.captures_iter(line)
-                .map(|cap| {
-                    let element = cap.get(1).unwrap().as_str();
-                    (element, DeviceType::Chip)
-                });
+                .map(|cap| (cap.group_str(1).unwrap(), DeviceType::Chip));
             let generators = GENERATOR_PATTERN.captures_iter(line)
-                .map(|cap| {
-                    let element = cap.get(1).unwrap().as_str();
-                    (element, DeviceType::Generator)
-                });
+                .map(|cap| (cap.group_str(1).unwrap(), DeviceType::Generator));
             chips.chain(generators).map(|(element, kind)| {
                 let element_id = get_element_id(element);
                 Device { element: element_id, kind }
@@ -139,45 +133,18 @@ impl AocDay for Day11 {
     }
 
     fn part1(input: &Self::I) -> Self::O {
-        let initial_state = input.clone();
-        let mut open_set = VecDeque::new();
-        open_set.push_back(initial_state.clone());
-        let mut visited: HashSet<State> = HashSet::new();
-
-        let mut costs: HashMap<State, usize> = HashMap::new();
-        costs.insert(initial_state, 0);
-
         let progress_bar = ProgressBar::no_length().with_style(
             ProgressStyle::with_template("[{elapsed_precise}] {pos:>7}").unwrap()
         );
 
-        while let Some(state) = open_set.pop_front() {
-            if visited.contains(&state) { continue; }
-
-            
-            progress_bar.inc(1);
-            let cost = *costs.get(&state).unwrap();
-            
-            if state.is_done() { return cost }
-
-            let next_states = state.next_states();
-            for state in next_states {
-                if let Some(existing_cost) = costs.get(&state) {
-                    let new_cost = cost + 1;
-                    if new_cost < *existing_cost {
-                        costs.insert(state.clone(), cost + 1);
-                    }
-                } else {
-                    costs.insert(state.clone(), cost + 1);
-                }
-                open_set.push_back(state);
-            }
-
-
-            visited.insert(state.clone());
-                        
-        }
-        panic!("No solution found");
+        dijkstra_cost(
+            input.clone(),
+            |state| {
+                progress_bar.inc(1);
+                state.next_states().into_iter().map(|next| (next, 1))
+            },
+            |state| state.is_done(),
+        ).expect("No solution found")
     }
 
     fn part2(input: &Self::I) -> Self::O {
@@ -1,126 +1,30 @@
-use std::collections::HashMap;
-
-use crate::day::AocDay;
-
-#[derive(Clone, Copy)]
-pub enum Value {
-    Register(char),
-    Constant(i32)
-}
-
-impl Value {
-    fn parse(text: &str) -> Self {
-        match text.parse() {
-            Ok(val) => Self::Constant(val),
-            Err(_) => Self::Register(text.chars().nth(0).unwrap())
-        }
-    }
-}
-
-pub enum Instruction {
-    Cpy{ src: Value, dst: Value },
-    Inc(char),
-    Dec(char),
-    Jnz{ test: Value, offset: Value },
-    Tgl(char),
-}
-
-type State = HashMap<char, i32>;
-fn initial_state() -> State {
-    vec![
-        ('a', 0),
-        ('b', 0),
-        ('c', 0),
-        ('d', 0),
-    ].into_iter().collect()
-}
-
-fn run_instructions(state: State, instructions: &Vec<Instruction>) -> State {
-    let mut state = state;
-    let mut program_counter = 0;
-    
-    while let Some(instruction) = instructions.get(program_counter) {
-        match instruction {
-            Instruction::Cpy { src, dst } => {
-                if let Value::Register(dst) = dst{
-                    let value = match src {
-                        Value::Register(r) => state[r],
-                        Value::Constant(v) => *v,
-                    };
-                    state.insert(*dst, value);
-                    program_counter += 1;
-                }
-            },
-            Instruction::Inc(reg) => {
-                state.insert(*reg, state[reg] + 1);
-                program_counter += 1;
-            },
-            Instruction::Dec(reg) => {
-                state.insert(*reg, state[reg] - 1);
-                program_counter += 1;
-            },
-            Instruction::Jnz { test, offset } => {
-                let value = match test {
-                    Value::Register(r) => state[r],
-                    Value::Constant(v) => *v,
-                };
-                if value == 0 {
-                    program_counter += 1;
-                } else {
-                    let off = match offset {
-                        Value::Register(r) => state[r],
-                        Value::Constant(v) => *v,
-                    };
-                    program_counter = program_counter.strict_add_signed(off as isize);
-                }
-            },
-            Instruction::Tgl(_) => {}, // Not supported for Day 12
-        }
-    }
-    state
-}
+use crate::{assembunny::{Instruction, Machine, instruction}, day::AocDay, utils::parser::parse_lines};
 
 pub struct Day12;
 impl AocDay for Day12 {
     type I = Vec<Instruction>;
 
-    type O = i32;
+    type O = i64;
 
     fn filename() -> &'static str {
         "input/day12.txt"
     }
 
     fn parse(contents: &str) -> Self::I {
-        contents.lines().map(|line| {
-            let splitted: Vec<_> = line.split_ascii_whitespace().collect();
-            match splitted[0] {
-                "cpy" => Instruction::Cpy { 
-                    src: Value::parse(splitted[1]),
-                    dst: Value::parse(splitted[2])
-                },
-                "jnz" => Instruction::Jnz { 
-                    test: Value::parse(splitted[1]),
-                    offset: Value::parse(splitted[2]),
-                },
-                "inc" => Instruction::Inc(splitted[1].chars().nth(0).unwrap()),
-                "dec" => Instruction::Dec(splitted[1].chars().nth(0).unwrap()),
-                "tgl" => Instruction::Tgl(splitted[1].chars().nth(0).unwrap()),
-                _ => panic!("Unknown opcode {}", splitted[0])
-            }
-        }).collect()
+        parse_lines(&instruction(), contents).unwrap()
     }
 
     fn part1(input: &Self::I) -> Self::O {
-        let state = initial_state();
-        let state = run_instructions(state, input);
-        *state.get(&'a').unwrap()
+        let mut machine = Machine::new(input.clone()).optimized();
+        machine.run();
+        machine.register('a')
     }
 
     fn part2(input: &Self::I) -> Self::O {
-        let mut state = initial_state();
-        state.insert('c', 1);
-        let state = run_instructions(state, input);
-        *state.get(&'a').unwrap()
+        let mut machine = Machine::new(input.clone()).optimized();
+        machine.set_register('c', 1);
+        machine.run();
+        machine.register('a')
     }
 }
 
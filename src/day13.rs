@@ -1,8 +1,4 @@
-use std::{cmp::Reverse, collections::{HashMap, HashSet}};
-
-use priority_queue::PriorityQueue;
-
-use crate::day::AocDay;
+use crate::{day::AocDay, utils::search::{astar_cost, count_within}};
 
 pub struct Day13;
 
@@ -15,57 +11,24 @@ fn manhattan(start: (usize, usize), end: (usize, usize)) -> usize {
     start.0.abs_diff(end.0) + start.1.abs_diff(end.1)
 }
 
-fn navigate(n: usize, start: (usize, usize), end: (usize, usize)) -> usize {
-    let mut open_set = PriorityQueue::new();
-    open_set.push(start, Reverse(manhattan(start, end)));
-
-    let mut visited = HashSet::new();
-
-    let mut costs = HashMap::new();
-    costs.insert(start, 0);
-
-    while let Some((state, _)) = open_set.pop() {
-        if visited.contains(&state) { continue; }
-        visited.insert(state);
-
-        let cost = *costs.get(&state).unwrap();
-        if state == end {
-            return cost;
-        }
-
-        // -X
-        if state.0 > 0 {
-            let new_state = (state.0 - 1, state.1);
-            if valid_coordinate(n, new_state) {
-                costs.insert(new_state, cost + 1);
-                open_set.push(new_state, Reverse(cost + 1 + manhattan(new_state, end)));
-            }
-        }
-
-        // +X
-        let new_state = (state.0 + 1, state.1);
-        if valid_coordinate(n, new_state) {
-            costs.insert(new_state, cost + 1);
-            open_set.push(new_state, Reverse(cost + 1 + manhattan(new_state, end)));
-        }
-
-        // -Y
-        if state.1 > 0 {
-            let new_state = (state.0, state.1 - 1);
-            if valid_coordinate(n, new_state) {
-                costs.insert(new_state, cost + 1);
-                open_set.push(new_state, Reverse(cost + 1 + manhattan(new_state, end)));
-            }
-        }
-
-        // +Y
-        let new_state = (state.0, state.1 + 1);
-        if valid_coordinate(n, new_state) {
-            costs.insert(new_state, cost + 1);
-            open_set.push(new_state, Reverse(cost + 1 + manhattan(new_state, end)));
-        }
+fn neighbours(n: usize, (x, y): (usize, usize)) -> impl Iterator<Item=(usize, usize)> {
+    let mut candidates = vec![(x + 1, y), (x, y + 1)];
+    if x > 0 {
+        candidates.push((x - 1, y));
+    }
+    if y > 0 {
+        candidates.push((x, y - 1));
     }
-    panic!("No solution found!")
+    candidates.into_iter().filter(move |state| valid_coordinate(n, *state))
+}
+
+fn navigate(n: usize, start: (usize, usize), end: (usize, usize)) -> usize {
+    astar_cost(
+        start,
+        |state| neighbours(n, *state).map(|next| (next, 1)),
+        |state| manhattan(*state, end),
+        |state| *state == end,
+    ).expect("No solution found!")
 }
 
 impl AocDay for Day13 {
@@ -86,58 +49,22 @@ impl AocDay for Day13 {
     }
 
     fn part2(input: &Self::I) -> Self::O {
-        let start = (1, 1);
-        let mut visited = HashSet::new();
-        let mut open_set = vec![start];
-
-        let mut costs = HashMap::new();
-        costs.insert(start, 0_u32);
-
-        while let Some(state) = open_set.pop() {
-            if visited.contains(&state) { continue; }
-            visited.insert(state);
-
-            let cost = *costs.get(&state).unwrap();
-            if cost == 50 { continue; }
-
-            let mut new_states = vec![
-                (state.0 + 1, state.1),
-                (state.0, state.1 + 1)
-            ];
-            if state.0 > 0 {
-                new_states.push((state.0  - 1, state.1));
-            }
-            if state.1 > 0 {
-                new_states.push((state.0, state.1 - 1));
-            }
-
-            for new_state in new_states {
-                if valid_coordinate(*input, new_state) {
-                    match costs.get(&new_state) {
-                        Some(old_cost) => {
-                            if cost + 1 < *old_cost {
-                                costs.insert(new_state, cost + 1);
-                                visited.remove(&new_state);
-                            }
-                        },
-                        None => {
-                            costs.insert(new_state, cost + 1);
-                        }
-                    }
-                    open_set.push(new_state);
-                }
-            }
-        }
-        visited.len()
+        count_within((1, 1), |state| neighbours(*input, *state), 50)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day13::navigate;
+    use crate::{day13::{navigate, neighbours}, utils::search::count_within};
 
     #[test]
     fn test_part1() {
         assert_eq!(11, navigate(10, (1,1), (7, 4)))
     }
+
+    #[test]
+    fn test_part2() {
+        // Within 2 steps of (1, 1) the example office has (1, 1), (0, 1), (1, 2), (0, 0) and (2, 2) open
+        assert_eq!(5, count_within((1, 1), |state| neighbours(10, *state), 2));
+    }
 }
\ No newline at end of file
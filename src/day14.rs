@@ -1,97 +1,63 @@
-use std::collections::HashMap;
+use std::collections::VecDeque;
 
-use itertools::{Itertools, repeat_n};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
-use crate::day::AocDay;
+use crate::{day::AocDay, utils::hashing::{Digest, Nibbles, SaltedHasher, stretch}};
 
 pub struct Day14;
 
-fn has_triple(input: &str) -> Option<char> {
-    match input.chars().tuple_windows().find(|(a, b, c)| {
-        a == b && a == c
-    }) {
-        Some((c,_, _)) => Some(c),
-        None => None,
-    }
-}
-
-fn has_subsequence(input: &str, sequence: &str) -> bool {
-    input.contains(sequence)
+fn key_hash(hasher: &mut SaltedHasher, index: usize, stretch_count: usize) -> Digest {
+    stretch(hasher.hash(index as u64), stretch_count)
 }
 
-fn hash(
-    salt: &str, 
-    index: usize,
-    cache: &mut HashMap<usize, String>,
-) -> String {
-    match cache.get(&index) {
-        Some(hash) => hash.to_owned(),
-        None => {
-            let key = format!("{salt}{index}");
-            let hash = md5::compute(key);
-            let hash_text = format!("{hash:x}");
-            cache.insert(index, hash_text.clone());
-            hash_text
-        },
-    }
+/// Streams the indices of the one-time pad keys for a salt, in increasing order.
+/// Hashes are kept in a sliding window that is extended one window at a time, in parallel.
+pub struct KeyGenerator<'a> {
+    salt: &'a str,
+    stretch_count: usize,
+    window: usize,
+    /// Hashes of `next_index` and the indices after it
+    hashes: VecDeque<Digest>,
+    next_index: usize,
 }
 
-fn find_next_index_p1(salt: &str, i: usize) -> usize {
-    let mut index = i;
-    let mut cache: HashMap<_, _> = HashMap::new();
-    loop {
-        let h = hash(salt, index, &mut cache);
-        if let Some(c) = has_triple(&h) {
-            let subsequence: String = repeat_n(c, 5).collect();
-            for j in 1..=1000 {
-                let h2 = hash(salt, index + j, &mut cache);
-                if has_subsequence(&h2, &subsequence) {
-                    return index;
-                }
-            }
-        }
-        index += 1;
+impl<'a> KeyGenerator<'a> {
+    /// `stretch_count` is the number of extra hashing rounds, and `window` the number of following
+    /// hashes searched for a run of five. An empty window can't confirm any key, so it yields nothing.
+    pub fn new(salt: &'a str, stretch_count: usize, window: usize) -> Self {
+        Self { salt, stretch_count, window, hashes: VecDeque::new(), next_index: 0 }
     }
-}
 
-fn hash_p2(
-    salt: &str, 
-    index: usize,
-    cache: &mut HashMap<usize, String>,
-) -> String {
-    match cache.get(&index) {
-        Some(hash) => hash.to_owned(),
-        None => {
-            let mut key: String = format!("{salt}{index}");
-            for _ in 0..=2016 {
-                let hash = md5::compute(key);
-                key = format!("{hash:x}");
-            }
-            cache.insert(index, key.clone());
-            key
-        },
+    fn fill(&mut self) {
+        if self.hashes.len() > self.window { return; }
+        let start = self.next_index + self.hashes.len();
+        let hashes: Vec<_> = (start..start + self.window + 1).into_par_iter().map_init(
+            || SaltedHasher::new(self.salt),
+            |hasher, index| key_hash(hasher, index, self.stretch_count),
+        ).collect();
+        self.hashes.extend(hashes);
     }
 }
 
-fn find_next_index_p2(salt: &str, i: usize) -> usize {
-    let mut index = i;
-    let mut cache: HashMap<_, _> = HashMap::new();
-    loop {
-        let h = hash_p2(salt, index, &mut cache);
-        if let Some(c) = has_triple(&h) {
-            let subsequence: String = repeat_n(c, 5).collect();
-            for j in 1..=1000 {
-                let h2 = hash_p2(salt, index + j, &mut cache);
-                if has_subsequence(&h2, &subsequence) {
-                    return index;
-                }
+impl Iterator for KeyGenerator<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.window == 0 { return None; }
+        loop {
+            self.fill();
+            let hash = self.hashes.pop_front().unwrap();
+            let index = self.next_index;
+            self.next_index += 1;
+            if let Some(c) = hash.has_run(3)
+                && self.hashes.iter().take(self.window).any(|next| next.contains_run(c, 5))
+            {
+                return Some(index);
             }
         }
-        index += 1;
     }
 }
 
-
 impl AocDay for Day14 {
     type I = String;
 
@@ -106,28 +72,17 @@ impl AocDay for Day14 {
     }
 
     fn part1(input: &Self::I) -> Self::O {
-        let mut i = 0;
-        for _ in 0..64 {
-            i = find_next_index_p1(input, i+1);
-        }
-        i
+        KeyGenerator::new(input, 0, 1000).nth(63).unwrap()
     }
 
     fn part2(input: &Self::I) -> Self::O {
-        let mut i = 0;
-        for n in 1..=64 {
-            i = find_next_index_p2(input, i+1);
-            println!("Found {n}={i}");
-        }
-        i
+        KeyGenerator::new(input, 2016, 1000).nth(63).unwrap()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
-    use crate::{day::AocDay, day14::{Day14, hash_p2}};
+    use crate::{day::AocDay, day14::{Day14, KeyGenerator, key_hash}, utils::hashing::SaltedHasher};
 
     #[test]
     fn test_part1() {
@@ -136,7 +91,17 @@ mod tests {
 
     #[test]
     fn test_part2_hash() {
-        assert_eq!("a107ff634856bb300138cac6568c0f24", hash_p2("abc", 0, &mut HashMap::new()));
+        let hash = key_hash(&mut SaltedHasher::new("abc"), 0, 2016);
+        assert_eq!("a107ff634856bb300138cac6568c0f24", format!("{hash:x}"));
+    }
+
+    #[test]
+    fn test_key_generator() {
+        let keys: Vec<_> = KeyGenerator::new("abc", 0, 1000).take(2).collect();
+        assert_eq!(vec![39, 92], keys);
+        // A smaller window finds fewer keys
+        assert!(KeyGenerator::new("abc", 0, 10).next().unwrap() > 39);
+        assert_eq!(None, KeyGenerator::new("abc", 0, 0).next());
     }
 
     #[test]
@@ -1,9 +1,8 @@
-use std::{sync::LazyLock, usize};
+use std::sync::LazyLock;
 
-use itertools::Itertools;
 use regex::Regex;
 
-use crate::day::AocDay;
+use crate::{day::AocDay, utils::{number::crt, regex::LinePattern}};
 
 pub struct Day15;
 
@@ -17,10 +16,20 @@ static DISC_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(
     r#"Disc .+ has (\d+) positions; at time=0, it is at position (\d+)."#
 ).unwrap());
 
+/// The first time at which a capsule dropped falls through every disc, if there ever is one.
+fn first_alignment(discs: &[Disc]) -> Option<usize> {
+    // Disc i is reached at time + i + 1, where it has to be at position 0
+    let congruences: Vec<_> = discs.iter().enumerate().map(|(i, disc)| {
+        let count = disc.count as i64;
+        (-((disc.start + i + 1) as i64), count)
+    }).collect();
+    crt(&congruences).map(|(time, _)| time as usize)
+}
+
 impl AocDay for Day15 {
     type I = Vec<Disc>;
 
-    type O = usize;
+    type O = String;
 
     fn filename() -> &'static str {
         "input/day15.txt"
@@ -28,29 +37,16 @@ impl AocDay for Day15 {
 
     fn parse(contents: &str) -> Self::I {
         contents.lines().map(|line| {
-            let cap = DISC_PATTERN.captures(line).unwrap();
-            Disc {
-                count: cap.get(1).unwrap().as_str().parse().unwrap(),
-                start: cap.get(2).unwrap().as_str().parse().unwrap(),
-            }
+            let (count, start) = DISC_PATTERN.parse_match(line).unwrap();
+            Disc { count, start }
         }).collect()
     }
 
     fn part1(input: &Self::I) -> Self::O {
-        let discs: Vec<_> = input.iter().enumerate().map(|(i, disc)| {
-            (disc.count, (disc.start + i + 1) % disc.count)
-        }).sorted_by_key(|(c, _)| usize::MAX - c).collect();
-        let (first_count, first_pos) = &discs[0];
-        let mut time = (first_count - first_pos) % first_count;
-        loop {
-            let mut mapped_disks = discs.iter().map(|(count, pos)| {
-                (pos + time) % count
-            });
-            if mapped_disks.all(|pos| pos == 0) {
-                return time;
-            }
-            time += first_count;
-        }
+        first_alignment(input).map_or_else(
+            || String::from("The discs never line up within 2^63 seconds"),
+            |time| time.to_string(),
+        )
     }
 
     fn part2(input: &Self::I) -> Self::O {
@@ -62,12 +58,27 @@ impl AocDay for Day15 {
 
 #[cfg(test)]
 mod tests {
-    use crate::{day::AocDay, day15::Day15};
+    use crate::{day::AocDay, day15::{Day15, first_alignment}};
 
     #[test]
     fn test_part1() {
         let parsed = Day15::parse(r#"Disc #1 has 5 positions; at time=0, it is at position 4.
 Disc #2 has 2 positions; at time=0, it is at position 1."#);
-        assert_eq!(5, Day15::part1(&parsed));
+        assert_eq!("5", Day15::part1(&parsed));
+    }
+
+    #[test]
+    fn test_never_aligned() {
+        // The first disc is only open at even times, the second one only at odd times
+        let parsed = Day15::parse(r#"Disc #1 has 2 positions; at time=0, it is at position 1.
+Disc #2 has 4 positions; at time=0, it is at position 1."#);
+        assert_eq!(None, first_alignment(&parsed));
+        assert_eq!("The discs never line up within 2^63 seconds", Day15::part1(&parsed));
+
+        // These do line up, but only after far more than 2^63 seconds
+        let parsed = Day15::parse(r#"Disc #1 has 2147483647 positions; at time=0, it is at position 1.
+Disc #2 has 2147483648 positions; at time=0, it is at position 2.
+Disc #3 has 2147483649 positions; at time=0, it is at position 3."#);
+        assert_eq!("The discs never line up within 2^63 seconds", Day15::part1(&parsed));
     }
 }
\ No newline at end of file
@@ -4,43 +4,84 @@ use crate::day::AocDay;
 
 pub struct Day16;
 
-fn expand_step(line: Vec<bool>) -> Vec<bool> {
-    let a = line.iter().map(|v| *v);
-    let b = line.iter().rev().map(|v| !(*v));
-    let mid = once(false);
+/// The dragon curve data grown from an initial state, computed on demand instead of materialized.
+/// The data is the initial state `a` and its reversed inverse `b` alternating as `a ? b ? a ? b ...`,
+/// where the separators `?` follow the regular paperfolding sequence.
+pub struct DragonData {
+    initial: Vec<bool>,
+    /// Number of ones in the first `i` bits of the initial state
+    prefix_ones: Vec<u64>,
+}
 
-    a.chain(mid).chain(b).collect()
+/// The `n`th separator between copies of the initial state, counting from 1.
+fn separator(n: u64) -> bool {
+    (n >> n.trailing_zeros()) % 4 == 3
 }
 
-fn generate_length(init: Vec<bool>, size: usize) -> Vec<bool> {
-    let mut accumulator = init;
-    while accumulator.len() < size {
-        accumulator = expand_step(accumulator);
-    }
-    accumulator
+/// Number of ones among the first `n` separators.
+fn separator_ones(n: u64) -> u64 {
+    if n == 0 { 0 } else { (n + 1) / 4 + separator_ones(n / 2) }
 }
 
-fn generate_checksum(sequence: Vec<bool>) -> Vec<bool> {
-    let mut accumulator = sequence;
-    while accumulator.len() % 2 == 0 {
-        accumulator = accumulator.chunks(2).map(|x| {
-            x[0] == x[1]
-        }).collect();
+impl DragonData {
+    pub fn new(initial: &[bool]) -> Self {
+        let prefix_ones = once(0).chain(initial.iter().scan(0, |ones, bit| {
+            *ones += u64::from(*bit);
+            Some(*ones)
+        })).collect();
+        Self { initial: initial.to_vec(), prefix_ones }
+    }
+
+    fn segment_length(&self) -> u64 {
+        self.initial.len() as u64
+    }
+
+    pub fn bit(&self, n: u64) -> bool {
+        let length = self.segment_length();
+        let (segment, offset) = (n / (length + 1), n % (length + 1));
+        if offset == length {
+            separator(segment + 1)
+        } else if segment.is_multiple_of(2) {
+            self.initial[offset as usize]
+        } else {
+            !self.initial[(length - 1 - offset) as usize]
+        }
+    }
+
+    /// Number of ones in the first `n` bits of the data.
+    pub fn ones_before(&self, n: u64) -> u64 {
+        let length = self.segment_length();
+        let (segments, offset) = (n / (length + 1), n % (length + 1));
+        // Every pair of a and b holds exactly `length` ones between them
+        let mut ones = segments / 2 * length + separator_ones(segments);
+        let all = self.prefix_ones[length as usize];
+        if segments % 2 == 1 {
+            ones += all;
+            ones += offset - (all - self.prefix_ones[(length - offset) as usize]);
+        } else {
+            ones += self.prefix_ones[offset as usize];
+        }
+        ones
+    }
+
+    /// The checksum of the first `size` bits, one bit at a time.
+    /// Repeatedly comparing pairs reduces a block of `2^k` bits to a single bit, which is set
+    /// exactly when the block holds an even number of ones.
+    pub fn checksum(&self, size: u64) -> impl Iterator<Item=bool> + '_ {
+        let block = if size == 0 { 1 } else { 1 << size.trailing_zeros() };
+        (0..size / block).map(move |i| {
+            let ones = self.ones_before((i + 1) * block) - self.ones_before(i * block);
+            ones.is_multiple_of(2)
+        })
     }
-    accumulator
 }
 
-fn to_value(checksum: Vec<bool>) -> String {
-    checksum.into_iter().map(|c| {
+fn to_value(checksum: impl Iterator<Item=bool>) -> String {
+    checksum.map(|c| {
         if c {'1'} else {'0'}
     }).collect()
 }
 
-fn create_checksum(init: Vec<bool>, size: usize) -> Vec<bool> {
-    let expanded = generate_length(init, size);
-    generate_checksum(expanded.into_iter().take(size).collect())
-}
-
 impl AocDay for Day16 {
     type I = Vec<bool>;
 
@@ -55,21 +96,17 @@ impl AocDay for Day16 {
     }
 
     fn part1(input: &Self::I) -> Self::O {
-        let size = 272;
-        let checksum = create_checksum(input.clone(), size);
-        to_value(checksum)
+        to_value(DragonData::new(input).checksum(272))
     }
 
     fn part2(input: &Self::I) -> Self::O {
-        let size = 35651584;
-        let checksum = create_checksum(input.clone(), size);
-        to_value(checksum)
+        to_value(DragonData::new(input).checksum(35651584))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{day::AocDay, day16::{Day16, create_checksum, expand_step, generate_checksum}};
+    use crate::{day::AocDay, day16::{Day16, DragonData, to_value}};
 
     #[test]
     fn test_expand() {
@@ -80,22 +117,32 @@ mod tests {
             ("111100001010", "1111000010100101011110000"),
         ];
         for (start, end) in test_data {
-            let start = Day16::parse(start);
+            let data = DragonData::new(&Day16::parse(start));
             let end = Day16::parse(end);
-            assert_eq!(end, expand_step(start));
+            assert_eq!(end, (0..end.len() as u64).map(|n| data.bit(n)).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_ones_before() {
+        let data = DragonData::new(&Day16::parse("10011"));
+        let mut ones = 0;
+        for n in 0..1000 {
+            assert_eq!(ones, data.ones_before(n));
+            ones += u64::from(data.bit(n));
         }
     }
 
     #[test]
     fn test_checksum() {
-        let input = Day16::parse("110010110100");
-        assert_eq!(Day16::parse("100"), generate_checksum(input));
+        let input = DragonData::new(&Day16::parse("110010110100"));
+        assert_eq!("100", to_value(input.checksum(12)));
     }
 
     #[test]
     fn test_whole() {
-        let input = Day16::parse("10000");
-        let size = 20;
-        assert_eq!(Day16::parse("01100"), create_checksum(input, size));
+        let input = DragonData::new(&Day16::parse("10000"));
+        assert_eq!("01100", to_value(input.checksum(20)));
+        assert_eq!(1, input.checksum(1 << 40).count());
     }
-}
\ No newline at end of file
+}
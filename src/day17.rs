@@ -1,9 +1,104 @@
 use std::collections::VecDeque;
 
-use crate::{day::AocDay, utils::direction::Direction};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::{day::AocDay, utils::{direction::Direction, point::{Point, YAxis}, hashing::{Nibbles, hash_concat}, search::par_path_stats}};
 
 pub struct Day17;
 
+const DIRECTIONS: [(Direction, char); 4] = [
+    (Direction::North, 'U'),
+    (Direction::South, 'D'),
+    (Direction::West, 'L'),
+    (Direction::East, 'R'),
+];
+
+/// A grid of rooms whose doors open depending on the hash of the passcode and the path taken so far.
+#[derive(Clone, Copy, Debug)]
+pub struct Vault {
+    width: i64,
+    height: i64,
+    start: Point,
+    goal: Point,
+}
+
+/// Every way through a vault for one passcode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultPaths {
+    pub shortest: Option<String>,
+    pub longest: Option<usize>,
+    pub count: usize,
+}
+
+impl Default for Vault {
+    fn default() -> Self {
+        Self::new(4, 4, Point::ORIGIN, Point::new(3, 3))
+    }
+}
+
+impl Vault {
+    pub fn new(width: i64, height: i64, start: Point, goal: Point) -> Self {
+        Self { width, height, start, goal }
+    }
+
+    fn in_bounds(&self, position: Point) -> bool {
+        (0..self.width).contains(&position.x) && (0..self.height).contains(&position.y)
+    }
+
+    fn next_states(&self, passcode: &str, (position, path): &(Point, String)) -> Vec<(Point, String)> {
+        let doors = hash_concat(&[passcode.as_bytes(), path.as_bytes()]).door_open_bits();
+
+        DIRECTIONS
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (direction, direction_letter))| {
+                let open = doors & (1 << i) != 0;
+                let new_position = *position + direction.to_point(YAxis::Down);
+
+                if open && self.in_bounds(new_position) {
+                    Some((new_position, format!("{path}{direction_letter}")))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// The shortest sequence of moves from the start to the goal.
+    pub fn shortest_path(&self, passcode: &str) -> Option<String> {
+        let mut open_set = VecDeque::new();
+        open_set.push_back((self.start, "".to_string()));
+
+        while let Some(state) = open_set.pop_front() {
+            if state.0 == self.goal {
+                return Some(state.1)
+            }
+            open_set.extend(self.next_states(passcode, &state));
+        }
+        None
+    }
+
+    /// Walks every path from the start to the goal.
+    pub fn solve(&self, passcode: &str) -> VaultPaths {
+        let stats = par_path_stats(
+            (self.start, "".to_string()),
+            |state| self.next_states(passcode, state),
+            |(position, _)| *position == self.goal,
+            |_| false,
+        );
+        VaultPaths {
+            shortest: stats.shortest.and_then(|_| self.shortest_path(passcode)),
+            longest: stats.longest,
+            count: stats.count,
+        }
+    }
+
+    /// Solves the vault for every passcode in parallel.
+    pub fn solve_all(&self, passcodes: &[&str]) -> Vec<VaultPaths> {
+        passcodes.par_iter().map(|passcode| self.solve(passcode)).collect()
+    }
+}
+
 impl AocDay for Day17 {
     type I = String;
 
@@ -18,110 +113,17 @@ impl AocDay for Day17 {
     }
 
     fn part1(input: &Self::I) -> Self::O {
-        let mut open_set = VecDeque::new();
-        open_set.push_back(("".to_string(), 0, 0));
-        let destination = (3, 3);
-
-        let directions = vec![
-            (Direction::South, 'U'),
-            (Direction::North, 'D'),
-            (Direction::West, 'L'),
-            (Direction::East, 'R'),
-        ];
-
-        while let Some((path, x, y)) = open_set.pop_front() {
-            if (x, y) == destination {
-                return path.to_string()
-            }
-
-            let key = format!("{input}{path}");
-            let hash = md5::compute(&key);
-            let hash_text = format!("{hash:x}");
-
-            let new_states = hash_text
-                .chars()
-                .zip(directions.iter())
-                .filter_map(|(hash_c, (direction, direction_letter))| {
-                    let open = hash_c >= 'b';
-                    let offset = direction.offset();
-                    let new_position = (
-                        x + offset.0,
-                        y + offset.1
-                    );
-                    let in_bounds = new_position.0 >= 0 && new_position.0 < 4 && new_position.1 >= 0 && new_position.1 < 4;
-
-                    if open && in_bounds {
-                        Some((
-                            format!("{path}{direction_letter}"),
-                            new_position.0,
-                            new_position.1,
-                        ))
-                    } else {
-                        None
-                    }
-            });
-
-            open_set.extend(new_states);
-        }
-        panic!("No solution found")
+        Vault::default().shortest_path(input).expect("No solution found")
     }
 
     fn part2(input: &Self::I) -> Self::O {
-        let mut open_set = VecDeque::new();
-        open_set.push_back(("".to_string(), 0, 0));
-        let destination = (3, 3);
-
-        let directions = vec![
-            (Direction::South, 'U'),
-            (Direction::North, 'D'),
-            (Direction::West, 'L'),
-            (Direction::East, 'R'),
-        ];
-
-        let mut longest_path = 0;
-
-        while let Some((path, x, y)) = open_set.pop_front() {
-            if (x, y) == destination {
-                longest_path = longest_path.max(path.len());
-                continue;
-            }
-
-            let key = format!("{input}{path}");
-            let hash = md5::compute(&key);
-            let hash_text = format!("{hash:x}");
-
-            let new_states = hash_text
-                .chars()
-                .zip(directions.iter())
-                .filter_map(|(hash_c, (direction, direction_letter))| {
-                    let open = hash_c >= 'b';
-                    let offset = direction.offset();
-                    let new_position = (
-                        x + offset.0,
-                        y + offset.1
-                    );
-                    let in_bounds = new_position.0 >= 0 && new_position.0 < 4 && new_position.1 >= 0 && new_position.1 < 4;
-
-                    if open && in_bounds {
-                        Some((
-                            format!("{path}{direction_letter}"),
-                            new_position.0,
-                            new_position.1,
-                        ))
-                    } else {
-                        None
-                    }
-            });
-
-            open_set.extend(new_states);
-        }
-        format!("{longest_path}")
+        format!("{}", Vault::default().solve(input).longest.unwrap_or(0))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{day::AocDay, day17::Day17};
+    use crate::{day::AocDay, day17::{Day17, Vault}, utils::point::Point};
 
     #[test]
     fn test_part1() {
@@ -136,4 +138,18 @@ mod tests {
         assert_eq!("492", Day17::part2(&"kglvqrro".to_string()));
         assert_eq!("830", Day17::part2(&"ulqzkmiv".to_string()));
     }
+
+    #[test]
+    fn test_vault() {
+        let solutions = Vault::default().solve_all(&["hijkl", "ihgpwlah"]);
+        assert_eq!(0, solutions[0].count);
+        assert_eq!(None, solutions[0].shortest);
+        assert_eq!(Some("DDRRRD".to_string()), solutions[1].shortest);
+        assert_eq!(Some(370), solutions[1].longest);
+
+        let small = Vault::new(3, 3, Point::ORIGIN, Point::new(2, 2)).solve("kglvqrro");
+        assert_eq!(Some("DDUDRUDR".to_string()), small.shortest);
+        assert_eq!(Some(20), small.longest);
+        assert_eq!(7, small.count);
+    }
 }
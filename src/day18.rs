@@ -1,34 +1,121 @@
-use std::iter::once;
-
-use itertools::Itertools;
-
 use crate::day::AocDay;
 
 pub struct Day18;
 
-fn iterate(row: Vec<bool>) -> Vec<bool> {
-    let window = 
-        once(false)
-        .chain(row.into_iter())
-        .chain(once(false));
-    window.tuple_windows().map(|(a, b, c)| {
-        (a && b && !c) ||
-        (!a && b && c) ||
-        (a && !b && !c) ||
-        (!a && !b && c)
-    }).collect()
+/// A row of tiles packed into bits, with a bit set for every trap.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TrapRow {
+    words: Vec<u64>,
+    width: usize,
+}
+
+impl TrapRow {
+    fn from_tiles(tiles: &[bool]) -> Self {
+        let mut words = vec![0; tiles.len().div_ceil(64)];
+        for (i, _) in tiles.iter().enumerate().filter(|(_, trap)| **trap) {
+            words[i / 64] |= 1 << (i % 64);
+        }
+        Self { words, width: tiles.len() }
+    }
+
+    fn safe_tiles(&self) -> usize {
+        self.width - self.words.iter().map(|word| word.count_ones() as usize).sum::<usize>()
+    }
+
+    fn is_trap(&self, i: usize) -> bool {
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    /// A tile becomes a trap exactly when its left and right neighbours differ.
+    fn next(&self) -> TrapRow {
+        let count = self.words.len();
+        let mut words: Vec<u64> = (0..count).map(|i| {
+            let before = if i > 0 { self.words[i - 1] >> 63 } else { 0 };
+            let after = if i + 1 < count { self.words[i + 1] << 63 } else { 0 };
+            let left = (self.words[i] << 1) | before;
+            let right = (self.words[i] >> 1) | after;
+            left ^ right
+        }).collect();
+        if let Some(last) = words.last_mut() && !self.width.is_multiple_of(64) {
+            *last &= (1 << (self.width % 64)) - 1;
+        }
+        TrapRow { words, width: self.width }
+    }
+
+    /// The row `steps` rows further down, without visiting the rows in between.
+    /// Mirroring the row about both walls gives a ring of `2 * (width + 1)` tiles following the same rule,
+    /// and since the rule is linear, every tile 2^k rows later is the XOR of the tiles 2^k to its left and right.
+    fn advance(&self, mut steps: usize) -> TrapRow {
+        let len = 2 * (self.width + 1);
+        let mut ring = vec![false; len];
+        for i in (0..self.width).filter(|i| self.is_trap(*i)) {
+            ring[i + 1] = true;
+            ring[len - 1 - i] = true;
+        }
+        let mut shift = 1;
+        while steps > 0 {
+            if steps & 1 == 1 {
+                ring = (0..len).map(|i| ring[(i + len - shift) % len] ^ ring[(i + shift) % len]).collect();
+            }
+            shift = shift * 2 % len;
+            steps >>= 1;
+        }
+        TrapRow::from_tiles(&ring[1..=self.width])
+    }
+}
+
+/// After how many rows a row of `width` tiles starts repeating, and a multiple of the period it then repeats with,
+/// if that fits in a usize. Writing the ring size as 2^a * m with m odd, the rows repeat after 2^(a - 1) rows with a
+/// period dividing 2^a * (2^s - 1), where s is the smallest power with 2^s = ±1 (mod m) (Martin, Odlyzko and Wolfram).
+fn cycle_length(width: usize) -> (usize, Option<usize>) {
+    let len = 2 * (width + 1);
+    let a = len.trailing_zeros();
+    let m = len >> a;
+    let transient = 1 << (a - 1);
+    if m == 1 {
+        // Every row ends up without any traps
+        return (transient, Some(1));
+    }
+    let mut s = 1;
+    let mut power = 2 % m;
+    while power != 1 && power != m - 1 {
+        power = power * 2 % m;
+        s += 1;
+    }
+    let period = (s < usize::BITS).then(|| (1_usize << s) - 1).and_then(|p| p.checked_mul(1 << a));
+    (transient, period)
 }
 
-fn find_safe_tiles(first_row: &Vec<bool>, rows: usize) -> usize {
+/// Counts the safe tiles in the next `count` rows, starting with `row` and moving it past them.
+fn sum_rows(row: &mut TrapRow, count: usize) -> usize {
     let mut total = 0;
-    let mut row = first_row.clone();
-    for _ in 0..rows {
-        total += row.iter().filter(|v| !(**v)).count();
-        row = iterate(row);
+    for _ in 0..count {
+        total += row.safe_tiles();
+        *row = row.next();
     }
     total
 }
 
+/// Counts the safe tiles in the first `rows` rows.
+/// When a whole period of the rows fits in that, only one period is counted and the rest is extrapolated.
+fn find_safe_tiles(first_row: &[bool], rows: usize) -> usize {
+    let mut row = TrapRow::from_tiles(first_row);
+    let (transient, period) = cycle_length(row.width);
+    if let Some(period) = period
+        && transient + period <= rows
+    {
+        let start = row.advance(transient);
+        if start.advance(period) == start {
+            let total = sum_rows(&mut row, transient);
+            let remaining = rows - transient;
+            let rest = sum_rows(&mut row, remaining % period);
+            let cycle = rest + sum_rows(&mut row, period - remaining % period);
+            return total + remaining / period * cycle + rest;
+        }
+    }
+    sum_rows(&mut row, rows)
+}
+
 impl AocDay for Day18 {
     type I = Vec<bool>;
 
@@ -53,11 +140,68 @@ impl AocDay for Day18 {
 
 #[cfg(test)]
 mod tests {
-    use crate::{day::AocDay, day18::{Day18, find_safe_tiles}};
+    use crate::{day::AocDay, day18::{Day18, TrapRow, cycle_length, find_safe_tiles, sum_rows}};
 
     #[test]
     fn test_part1() {
         assert_eq!(6, find_safe_tiles(&Day18::parse("..^^."), 3));
         assert_eq!(38, find_safe_tiles(&Day18::parse(".^^.^.^^^^"), 10));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_next_row() {
+        let row = TrapRow::from_tiles(&Day18::parse(".^^.^.^^^^"));
+        assert_eq!(TrapRow::from_tiles(&Day18::parse("^^^...^..^")), row.next());
+
+        // Traps move across word boundaries
+        let mut tiles = vec![false; 130];
+        tiles[63] = true;
+        let next = TrapRow::from_tiles(&tiles).next();
+        let mut expected = vec![false; 130];
+        expected[62] = true;
+        expected[64] = true;
+        assert_eq!(TrapRow::from_tiles(&expected), next);
+        assert_eq!(130 - 2, next.next().safe_tiles());
+    }
+
+    #[test]
+    fn test_extrapolation() {
+        let first = Day18::parse(".^^.^.^^^^");
+        let mut row = TrapRow::from_tiles(&first);
+        let mut total = 0;
+        for rows in 1..200 {
+            total += row.safe_tiles();
+            row = row.next();
+            assert_eq!(total, find_safe_tiles(&first, rows));
+        }
+        // Only feasible because the rows repeat
+        assert!(find_safe_tiles(&first, 1_000_000_000_000) > 0);
+
+        // 40 tiles repeat every 2046 rows, so a few thousand rows are already extrapolated
+        let first: Vec<_> = (0..40).map(|i| i * i % 7 < 3).collect();
+        assert_eq!((1, Some(2046)), cycle_length(40));
+        for rows in [2047, 3000, 5000] {
+            assert_eq!(sum_rows(&mut TrapRow::from_tiles(&first), rows), find_safe_tiles(&first, rows));
+        }
+    }
+
+    #[test]
+    fn test_advance() {
+        let first: Vec<_> = (0..130).map(|i| i % 3 == 0 || i % 11 == 4).collect();
+        let start = TrapRow::from_tiles(&first);
+        let mut row = start.clone();
+        for steps in 0..300 {
+            assert_eq!(row, start.advance(steps));
+            row = row.next();
+        }
+
+        // 100 tiles take about 2^51 rows to repeat, which jumping confirms right away
+        let first: Vec<_> = (0..100).map(|i| i * i % 13 < 5).collect();
+        let (transient, period) = cycle_length(100);
+        assert_eq!((1, Some(2 * ((1 << 50) - 1))), (transient, period));
+        let start = TrapRow::from_tiles(&first).advance(transient);
+        assert_eq!(start, start.advance(period.unwrap()));
+        assert_ne!(start, start.advance(period.unwrap() / 2));
+        assert_eq!(sum_rows(&mut TrapRow::from_tiles(&first), 1000), find_safe_tiles(&first, 1000));
+    }
+}
@@ -1,19 +1,17 @@
-use crate::{day::AocDay, utils::direction::Direction};
+use crate::{day::AocDay, utils::{direction::Direction, point::{Point, YAxis}}};
 
 pub struct Day2;
 
 impl Day2 {
-    fn move_keypad(position: (i32, i32), moves: &Vec<Direction>) ->(i32, i32) {
+    fn move_keypad(position: Point, moves: &Vec<Direction>) -> Point {
         moves.iter().fold(position, |acc, direction| {
-            let offset = direction.offset();
-            let x = (acc.0 + offset.0).clamp(0, 2);
-            let y = (acc.1 + offset.1).clamp(0, 2);
-            (x, y)
+            let moved = acc + direction.to_point(YAxis::Up);
+            Point::new(moved.x.clamp(0, 2), moved.y.clamp(0, 2))
         })
     }
 
-    fn to_digit(position: (i32, i32)) -> i32 {
-        match position {
+    fn to_digit(position: Point) -> i32 {
+        match (position.x, position.y) {
             (0, 0) => 7,
             (1, 0) => 8,
             (2, 0) => 9,
@@ -27,21 +25,20 @@ impl Day2 {
         }
     }
     
-    fn move_keypad2(position: (i32, i32), moves: &Vec<Direction>) ->(i32, i32) {
+    fn move_keypad2(position: Point, moves: &Vec<Direction>) -> Point {
+        let center = Point::new(2, 2);
         moves.iter().fold(position, |acc, direction| {
-            let offset = direction.offset();
-            let x = acc.0 + offset.0 ;
-            let y = acc.1 + offset.1 ;
-            if ((x-2).abs() + (y-2).abs()) <= 2 {
-                (x, y)
+            let moved = acc + direction.to_point(YAxis::Up);
+            if moved.manhattan(&center) <= 2 {
+                moved
             } else {
                 acc
             }
         })
     }
 
-    fn to_digit2(position: (i32, i32)) -> u32 {
-        match position {
+    fn to_digit2(position: Point) -> u32 {
+        match (position.x, position.y) {
             (2, 0) => 0xD,
             (1, 1) => 0xA,
             (2, 1) => 0xB,
@@ -71,20 +68,14 @@ impl AocDay for Day2 {
     fn parse(contents: &str) -> Self::I {
         contents.lines().map(|line| {
             line.chars().map(|c| {
-                match c {
-                    'U' => Direction::North,
-                    'D' => Direction::South,
-                    'L' => Direction::West,
-                    'R' => Direction::East,
-                    _ => panic!("Unknown character {c}"),
-                }
+                Direction::from_char(c).unwrap_or_else(|| panic!("Unknown character {c}"))
             }).collect()
         }).collect()
     }
     
     fn part1(input: &Self::I) -> Self::O {
         let mut acc = 0;
-        let mut position = (1, 1);
+        let mut position = Point::new(1, 1);
         for moves in input {
             position = Self::move_keypad(position, moves);
             acc = acc * 10 + Self::to_digit(position);
@@ -94,7 +85,7 @@ impl AocDay for Day2 {
     
     fn part2(input: &Self::I) -> Self::O {
         let mut acc = 0;
-        let mut position = (1, 1);
+        let mut position = Point::new(1, 1);
         for moves in input {
             position = Self::move_keypad2(position, moves);
             acc = acc * 16 + Self::to_digit2(position);
@@ -1,35 +1,12 @@
 
-use crate::day::AocDay;
+use crate::{day::AocDay, utils::interval_set::IntervalSet};
 
 pub struct Day20;
 
-fn segment_without(segment: (u32, u32), range: &(u32, u32)) -> Vec<(u32, u32)> {
-    // Cases:
-    if range.1 < segment.0 {
-        // Range is before segment
-        vec![segment]
-    } else if range.0 > segment.1 {
-        // Range is after segment
-        vec![segment]
-    } else if range.0 <= segment.0 && range.1 >= segment.1 {
-        // Range wraps around segment
-        vec![]
-    } else if range.0 <= segment.0 && range.1 < segment.1 {
-        // Range contains start of segment
-        vec![(range.1 + 1, segment.1)]
-    } else if range.0 > segment.0 && range.1 >= segment.1 {
-        // Range contains end of segment
-        vec![(segment.0, range.0 - 1)]
-    } else {
-        // Range is within segment
-        vec![(segment.0, range.0 - 1), (range.1 + 1, segment.1)]
-    }
-}
-
 impl AocDay for Day20 {
-    type I = Vec<(u32, u32)>;
+    type I = IntervalSet<u32>;
 
-    type O = u32;
+    type O = u64;
 
     fn filename() -> &'static str {
         "input/day20.txt"
@@ -43,23 +20,12 @@ impl AocDay for Day20 {
     }
 
     fn part1(input: &Self::I) -> Self::O {
-        input.iter().fold(vec![(0_u32, u32::MAX)], |segments, range| {
-            segments
-                .into_iter()
-                .flat_map(|segment| segment_without(segment, &range))
-                .collect()
-        }).first().unwrap().0
+        input.first_gap(0, u32::MAX).unwrap().into()
     }
 
     fn part2(input: &Self::I) -> Self::O {
-        input.iter().fold(vec![(0_u32, u32::MAX)], |segments, range| {
-            segments
-                .into_iter()
-                .flat_map(|segment| segment_without(segment, &range))
-                .collect()
-        }).iter().map(|segment| {
-            segment.1 - segment.0 + 1
-        }).sum()
+        // At most 2^32 addresses are allowed, which doesn't fit in a u32 but always fits in a u64
+        input.count_uncovered(0, u32::MAX) as u64
     }
 }
 
@@ -74,4 +40,15 @@ mod tests {
 4-7"#);
         assert_eq!(3, Day20::part1(&ranges))
     }
+
+    #[test]
+    fn test_part2() {
+        let ranges = Day20::parse(r#"5-8
+0-2
+4-7"#);
+        assert_eq!(u64::from(u32::MAX) - 7, Day20::part2(&ranges));
+
+        // Nothing blocked leaves every single address allowed
+        assert_eq!(1 << 32, Day20::part2(&Day20::parse("")));
+    }
 }
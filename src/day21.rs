@@ -1,10 +1,9 @@
-use core::panic;
 use std::{collections::VecDeque, iter::once, sync::LazyLock};
 
 use itertools::Itertools;
 use regex::Regex;
 
-use crate::day::AocDay;
+use crate::{day::AocDay, utils::{error::ParseError, regex::LinePattern}};
 
 pub struct Day21;
 
@@ -166,6 +165,28 @@ static MOVE_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(
     r#"move position (\d+) to position (\d+)"#
 ).unwrap());
 
+impl Operation {
+    fn parse(line: &str) -> Result<Operation, ParseError> {
+        if let Some((a, b)) = SWAP_POS_PATTERN.parse_captures(line)? {
+            Ok(Operation::SwapPos { a, b })
+        } else if let Some((a, b)) = SWAP_CHAR_PATTERN.parse_captures(line)? {
+            Ok(Operation::SwapChar { a, b })
+        } else if let Some((n,)) = ROTATE_L_PATTERN.parse_captures(line)? {
+            Ok(Operation::RotateL { n })
+        } else if let Some((n,)) = ROTATE_R_PATTERN.parse_captures(line)? {
+            Ok(Operation::RotateR { n })
+        } else if let Some((c,)) = ROTATE_POS_PATTERN.parse_captures(line)? {
+            Ok(Operation::RotatePos { c })
+        } else if let Some((a, b)) = REVERSE_PATTERN.parse_captures(line)? {
+            Ok(Operation::Reverse { a, b })
+        } else if let Some((a, b)) = MOVE_PATTERN.parse_captures(line)? {
+            Ok(Operation::Move { a, b })
+        } else {
+            Err(ParseError::new(format!("Could not parse line {line}")))
+        }
+    }
+}
+
 impl AocDay for Day21 {
     type I = Vec<Operation>;
 
@@ -177,34 +198,7 @@ impl AocDay for Day21 {
 
     fn parse(contents: &str) -> Self::I {
         contents.lines().map(|line| {
-            if let Some(cap) = SWAP_POS_PATTERN.captures(line) {
-                let a = cap.get(1).unwrap().as_str().parse().unwrap();
-                let b = cap.get(2).unwrap().as_str().parse().unwrap();
-                Operation::SwapPos { a, b }
-            } else if let Some(cap) = SWAP_CHAR_PATTERN.captures(line) {
-                let a = cap.get(1).unwrap().as_str().chars().nth(0).unwrap();
-                let b = cap.get(2).unwrap().as_str().chars().nth(0).unwrap();
-                Operation::SwapChar { a, b }
-            } else if let Some(cap) = ROTATE_L_PATTERN.captures(line) {
-                let n = cap.get(1).unwrap().as_str().parse().unwrap();
-                Operation::RotateL { n }
-            } else if let Some(cap) = ROTATE_R_PATTERN.captures(line) {
-                let n = cap.get(1).unwrap().as_str().parse().unwrap();
-                Operation::RotateR { n }
-            } else if let Some(cap) = ROTATE_POS_PATTERN.captures(line) {
-                let c = cap.get(1).unwrap().as_str().chars().nth(0).unwrap();
-                Operation::RotatePos { c }
-            } else if let Some(cap) = REVERSE_PATTERN.captures(line) {
-                let a = cap.get(1).unwrap().as_str().parse().unwrap();
-                let b = cap.get(2).unwrap().as_str().parse().unwrap();
-                Operation::Reverse { a, b }
-            } else if let Some(cap) = MOVE_PATTERN.captures(line) {
-                let a = cap.get(1).unwrap().as_str().parse().unwrap();
-                let b = cap.get(2).unwrap().as_str().parse().unwrap();
-                Operation::Move { a, b }
-            } else {
-                panic!("Could not parse line {line}")
-            }
+            Operation::parse(line).unwrap()
         }).collect()
     }
 
@@ -1,12 +1,7 @@
 
-use std::{cmp::Reverse, collections::HashMap, hash::Hash};
-
-use indicatif::{ProgressBar, ProgressStyle};
 use itertools::Itertools;
-use priority_queue::PriorityQueue;
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
-use crate::{day::AocDay, utils::grid::Grid};
+use crate::{day::AocDay, utils::{grid::Grid, search::astar_cost, parser::{Parser, integer, literal, parse_all, take_while, whitespace}}};
 
 pub struct Day22;
 
@@ -16,86 +11,23 @@ pub struct Node {
     used: u32,
 }
 
-fn available_moves(
-    sizes: &Grid<u32>,
-    used: &Grid<u32>
-) -> Vec<((usize, usize), (usize, usize))> {
-    (0..(sizes.width*sizes.height))
-        .into_par_iter()
-        .flat_map(|i| {
-            let x = i % sizes.width;
-            let y = i / sizes.width;
-
-            let size_a = sizes.get_unchecked(x, y);
-            let used_a = *used.get_unchecked(x, y);
-            let mut all_moves = Vec::with_capacity(4);
-
-            // Horizontal
-            if x < sizes.width - 1 {
-                let size_h = sizes.get_unchecked(x + 1, y);
-                let used_h = *used.get_unchecked(x + 1, y);
-                
-                if used_h == 0 && used_a <= *size_h {
-                    all_moves.push(((x, y),(x + 1, y)));
-                }
-                if used_a == 0 && used_h <= *size_a {
-                    all_moves.push(((x + 1, y),(x, y)));
-                }
-            }
-
-            // Vertical
-            if y < sizes.height - 1 {
-                let size_v = sizes.get_unchecked(x, y + 1);
-                let used_v = *used.get_unchecked(x, y + 1);
-                
-                if used_v == 0 && used_a <= *size_v {
-                    all_moves.push(((x, y),(x, y + 1)));
-                }
-                if used_a == 0 && used_v <= *size_a {
-                    all_moves.push(((x, y + 1),(x, y)));
-                }
-            }
-            all_moves
-        })
-        .collect()
+fn node<'a>() -> impl Parser<'a, ((usize, usize), Node)> {
+    let terabytes = || whitespace().ignore_then(integer()).then_ignore(literal("T"));
+    literal("/dev/grid/node-x").ignore_then(integer())
+        .then_ignore(literal("-y")).then(integer())
+        .then(terabytes())
+        .then(terabytes())
+        .then_ignore(take_while(|_| true))
+        .map(|((position, size), used)| (position, Node { size, used }))
 }
 
 fn find_hole(
-    used: &Grid<u32>
+    grid: &Grid<Node>
 ) -> (usize, usize) {
-    for y in 0..used.height {
-        for x in 0..used.width{
-            if *used.get_unchecked(x, y) == 0 {
-                return (x, y);
-            }
-        }
-    }
-    panic!("No hole found!");
-}
-
-fn print_grid(
-    grid: &Grid<Node>,
-) {
-    let target = (grid.width-1, 0);
-    let target_node = grid.get_unchecked(target.0, target.1);
-
-    for y in 0..grid.height {
-        for x in 0..grid.width {
-            let node = grid.get_unchecked(x, y);
-            if x == target.0 && y == target.1 {
-                print!("G");
-            } else if node.used == 0 {
-                print!("_")
-            } else if node.size > 200 {
-                print!("#")
-            } else if node.size < target_node.used {
-                print!("+")
-            } else {
-                print!(".")
-            }
-        }
-        print!("\n")
-    }
+    grid.iter_coords()
+        .find(|(_, _, node)| node.used == 0)
+        .map(|(x, y, _)| (x, y))
+        .expect("No hole found!")
 }
 
 impl AocDay for Day22 {
@@ -109,17 +41,7 @@ impl AocDay for Day22 {
 
     fn parse(contents: &str) -> Self::I {
         let nodes: Vec<_> = contents.lines().skip(2).map(|line| {
-            let mut splitted = line.split_ascii_whitespace();
-            let location_text = splitted.next().unwrap();
-            let mut location_splitted = location_text.split("-").skip(1);
-            let x = (&(location_splitted.next().unwrap())[1..]).parse().unwrap();
-            let y = (&(location_splitted.next().unwrap())[1..]).parse().unwrap();
-            let size_text = splitted.next().unwrap();
-            let size = (&size_text[..size_text.len()-1]).parse().unwrap();
-            let used_text = splitted.next().unwrap();
-            let used = (&used_text[..used_text.len()-1]).parse().unwrap();
-            let node = Node { size, used };
-            ((x, y), node)
+            parse_all(&node(), line).unwrap()
         }).collect();
         let max_width = *nodes.iter().map(|((x, _), _)| x).max().unwrap();
         let max_height = *nodes.iter().map(|((_, y), _)| y).max().unwrap();
@@ -147,18 +69,25 @@ impl AocDay for Day22 {
     }
 
     fn part2(input: &Self::I) -> Self::O {
-        print_grid(input);
-
-        let hole = (20, 25);
-        let target = (33, 0);
-        let left_edge = 4;
-
-        let move_left = hole.0 - left_edge;
-        let move_up = hole.1 - target.1;
-        let move_right = target.0 - left_edge;
-        let cycle = 5;
-        let all_cycles = target.0 -1;
-        return move_left + move_up + move_right + cycle*all_cycles;
+        // Only the empty node moves around, swapping places with the data next to it.
+        // Nodes holding more than the empty node can store act as walls.
+        let hole = find_hole(input);
+        let capacity = input[hole].size;
+        let target = (input.width - 1, 0);
+
+        astar_cost(
+            (hole, target),
+            |&(hole, data)| {
+                input.direct_neighbours(hole.0, hole.1)
+                    .filter(|(_, _, node)| node.used <= capacity)
+                    .map(move |(x, y, _)| {
+                        let data = if (x, y) == data { hole } else { data };
+                        (((x, y), data), 1)
+                    })
+            },
+            |(_, data)| data.0 + data.1,
+            |(_, data)| *data == (0, 0),
+        ).expect("No solution found")
     }
 }
 
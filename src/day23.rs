@@ -1,183 +1,10 @@
-use std::{collections::HashMap, sync::Arc};
-
-use crate::{day::AocDay, day12::{Day12, Instruction, Value}};
-
-type State = HashMap<char, i32>;
-fn initial_state() -> State {
-    vec![
-        ('a', 0),
-        ('b', 0),
-        ('c', 0),
-        ('d', 0),
-    ].into_iter().collect()
-}
-
-fn run_instructions(
-    initial_state: State, 
-    instructions: &Vec<Instruction>,
-    program_counter: usize,
-    breakpoints: &Vec<usize>,
-) -> State {
-    let mut state = initial_state;
-    let mut toggles: HashMap<usize, Instruction> = HashMap::new();
-    let mut program_counter = program_counter;
-
-    while let Some(instruction) = toggles.get(&program_counter).or(instructions.get(program_counter)) {
-        if breakpoints.contains(&(program_counter+1)) {
-            return state
-        }
-        match instruction {
-            Instruction::Cpy { src, dst } => {
-                if let Value::Register(dst) = dst{
-                    let value = match src {
-                        Value::Register(r) => state[r],
-                        Value::Constant(v) => *v,
-                    };
-                    state.insert(*dst, value);
-                    program_counter += 1;
-                }
-            },
-            Instruction::Inc(reg) => {
-                state.insert(*reg, state[reg] + 1);
-                program_counter += 1;
-            },
-            Instruction::Dec(reg) => {
-                state.insert(*reg, state[reg] - 1);
-                program_counter += 1;
-            },
-            Instruction::Jnz { test, offset } => {
-                let value = match test {
-                    Value::Register(r) => state[r],
-                    Value::Constant(v) => *v,
-                };
-                if value == 0 {
-                    program_counter += 1;
-                } else {
-                    let off = match offset {
-                        Value::Register(r) => state[r],
-                        Value::Constant(v) => *v,
-                    };
-                    program_counter = program_counter.strict_add_signed(off as isize);
-                }
-            },
-            Instruction::Tgl(offset) => {
-                let value = program_counter as i32 + state[offset];
-                if value >= 0 {
-                    if let Some(instruction) = toggles.get(&(value as usize)).or(instructions.get(value as usize)) {
-                        let new_instruction = match instruction {
-                            Instruction::Inc(r) => Instruction::Dec(*r),
-                            Instruction::Dec(r) => Instruction::Inc(*r),
-                            Instruction::Tgl(r) => Instruction::Inc(*r),
-                            Instruction::Cpy { src, dst } => Instruction::Jnz { test: *src, offset: dst.clone() },
-                            Instruction::Jnz { test, offset } => Instruction::Cpy { src: test.clone(), dst: offset.clone()},
-                        };
-                        toggles.insert(value as usize, new_instruction);
-                    }
-                }
-                program_counter += 1;
-            },
-        }
-    }
-    state
-}
-
-fn run_with_overrides(
-    initial_state: State, 
-    instructions: &Vec<Instruction>,
-) -> State {
-    let mut state = initial_state.clone();
-    let mut toggles: HashMap<usize, Instruction> = HashMap::new();
-    let mut program_counter = 0;
-    let run_until = 10000;
-
-    while let Some(instruction) = toggles.get(&program_counter).or(instructions.get(program_counter)) {
-        if (program_counter+1) >= run_until {
-            let expected_state = run_instructions(initial_state.clone(), instructions, 0, &vec![run_until]);
-            println!("PC: {program_counter} state: {state:?}");
-            println!("Expected: {expected_state:?}");
-            println!("Is same {}", state == expected_state);
-            panic!("Found run until");
-        }
-
-        match program_counter+1 {
-            6 => {
-                state.insert('a', state[&'a'] + state[&'c']);
-                state.insert('c', 0);
-                program_counter = 9 -1;
-                continue;
-            }
-            5 => {
-                state.insert('a', 
-                    state[&'a'] + state[&'b'] * state[&'d']
-                );
-                state.insert('d', 0);
-                state.insert('c', 0);
-                program_counter = 10 -1;
-                continue;
-            }
-            _ => {}
-        }
-
-        match instruction {
-            Instruction::Cpy { src, dst } => {
-                if let Value::Register(dst) = dst{
-                    let value = match src {
-                        Value::Register(r) => state[r],
-                        Value::Constant(v) => *v,
-                    };
-                    state.insert(*dst, value);
-                    program_counter += 1;
-                }
-            },
-            Instruction::Inc(reg) => {
-                state.insert(*reg, state[reg] + 1);
-                program_counter += 1;
-            },
-            Instruction::Dec(reg) => {
-                state.insert(*reg, state[reg] - 1);
-                program_counter += 1;
-            },
-            Instruction::Jnz { test, offset } => {
-                let value = match test {
-                    Value::Register(r) => state[r],
-                    Value::Constant(v) => *v,
-                };
-                if value == 0 {
-                    program_counter += 1;
-                } else {
-                    let off = match offset {
-                        Value::Register(r) => state[r],
-                        Value::Constant(v) => *v,
-                    };
-                    program_counter = program_counter.strict_add_signed(off as isize);
-                }
-            },
-            Instruction::Tgl(offset) => {
-                let value = program_counter as i32 + state[offset];
-                if value >= 0 {
-                    if let Some(instruction) = toggles.get(&(value as usize)).or(instructions.get(value as usize)) {
-                        let new_instruction = match instruction {
-                            Instruction::Inc(r) => Instruction::Dec(*r),
-                            Instruction::Dec(r) => Instruction::Inc(*r),
-                            Instruction::Tgl(r) => Instruction::Inc(*r),
-                            Instruction::Cpy { src, dst } => Instruction::Jnz { test: *src, offset: dst.clone() },
-                            Instruction::Jnz { test, offset } => Instruction::Cpy { src: test.clone(), dst: offset.clone()},
-                        };
-                        toggles.insert(value as usize, new_instruction);
-                    }
-                }
-                program_counter += 1;
-            },
-        }
-    }
-    state
-}
+use crate::{assembunny::{Instruction, Machine}, day::AocDay, day12::Day12};
 
 pub struct Day23;
 impl AocDay for Day23 {
     type I = Vec<Instruction>;
 
-    type O = i32;
+    type O = i64;
 
     fn filename() -> &'static str {
         "input/day23.txt"
@@ -188,23 +15,25 @@ impl AocDay for Day23 {
     }
 
     fn part1(input: &Self::I) -> Self::O {
-        let mut state = initial_state();
-        state.insert('a', 7);
-        let end_state = run_instructions(state, input, 0, &vec![]);
-        *end_state.get(&'a').unwrap()
+        let mut machine = Machine::new(input.clone());
+        machine.set_register('a', 7);
+        machine.run();
+        machine.register('a')
     }
 
     fn part2(input: &Self::I) -> Self::O {
-        let mut state = initial_state();
-        state.insert('a', 12);
-        let end_state = run_with_overrides(state, input);
-        *end_state.get(&'a').unwrap()
+        let mut machine = Machine::new(input.clone()).optimized();
+        machine.set_register('a', 12);
+        machine.run();
+        machine.register('a')
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{day::AocDay, day23::{Day23, run_instructions}};
+    use std::{fs, time::{Duration, Instant}};
+
+    use crate::{assembunny::Machine, day::AocDay, day23::Day23};
 
     #[test]
     fn test_day1() {
@@ -219,4 +48,18 @@ dec a"#;
         let parsed = Day23::parse(input);
         assert_eq!(3, Day23::part1(&parsed));
     }
-}
\ No newline at end of file
+
+    /// Part 2 without the optimizer, relying on the register array and in-place toggling alone.
+    /// Takes about 18s in release on a single core, so run it with `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_part2_unoptimized() {
+        let parsed = Day23::parse(&fs::read_to_string(Day23::filename()).unwrap());
+        let start = Instant::now();
+        let mut machine = Machine::new(parsed);
+        machine.set_register('a', 12);
+        machine.run();
+        assert_eq!(479009040, machine.register('a'));
+        assert!(start.elapsed() < Duration::from_secs(60));
+    }
+}
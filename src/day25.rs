@@ -1,44 +1,86 @@
-use crate::day::AocDay;
+use std::collections::HashSet;
+
+use crate::{assembunny::{Instruction, Machine, instruction}, day::AocDay, utils::parser::parse_lines};
 
 pub struct Day25;
 
-impl Day25 {
-    fn run_machine(x: i32) {
-        let max_prints = 40;
-        let mut prints = 0;
-
-        let mut a = x;
-        let mut b = 0;
-        let mut c = 0;
-        let mut d = 0;
-        
-        // Line 1-2
-        d = a;
-        c = 4;
-
-        // Line 3-8
-        d += 633 * c;
-        println!("{d} {d:b}");
-
-        loop {
-            a = d;
-
-            while a != 0  {
-                b = a % 2;
-                a = a / 2;
-
-                print!("{b}");
-                prints += 1;
-                if prints >= max_prints {
-                    return;
-                }
+/// Whether the program sends `0, 1, 0, 1, ...` forever when started with `a` in register a.
+/// The signal is only accepted once the machine returns to a state it was in after an earlier output,
+/// which proves it repeats. Gives up after `max_steps` instructions.
+fn is_clock_signal(program: &[Instruction], a: i64, max_steps: usize) -> bool {
+    let mut machine = Machine::new(program.to_vec());
+    machine.set_register('a', a);
+    let mut seen = HashSet::new();
+    let mut expected = 0;
+
+    for _ in 0..max_steps {
+        let running = machine.step();
+        if let Some(value) = machine.output() {
+            if value != expected {
+                return false;
             }
+            expected = 1 - expected;
+            if !seen.insert((machine.snapshot(), expected)) {
+                return true;
+            }
+        }
+        if !running {
+            return false;
         }
     }
+    false
+}
+
+/// The lowest value of register a from 1 to `max_input` that makes the program send a clock signal.
+fn find_clock_input(program: &[Instruction], max_input: i64, max_steps: usize) -> Option<i64> {
+    (1..=max_input).find(|a| is_clock_signal(program, *a, max_steps))
+}
+
+impl AocDay for Day25 {
+    type I = Vec<Instruction>;
+
+    type O = i64;
+
+    fn filename() -> &'static str {
+        "input/day25.txt"
+    }
+
+    fn parse(contents: &str) -> Self::I {
+        parse_lines(&instruction(), contents).unwrap()
+    }
+
+    fn part1(input: &Self::I) -> Self::O {
+        find_clock_input(input, 100_000, 1_000_000).expect("No input produces a clock signal")
+    }
+
+    fn part2(_input: &Self::I) -> Self::O {
+        // There is no second puzzle on the last day
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{day::AocDay, day25::{Day25, find_clock_input, is_clock_signal}};
 
-    pub fn run() {
-        println!("{}", 0b11000110);
-        Self::run_machine(0b11000110);
+    #[test]
+    fn test_part1() {
+        let program = Day25::parse(r#"dec a
+dec a
+dec a
+out a
+inc a
+out a
+dec a
+jnz 1 -4"#);
+        assert!(!is_clock_signal(&program, 2, 1000));
+        assert!(!is_clock_signal(&program, 4, 1000));
+        assert_eq!(3, Day25::part1(&program));
 
+        let halting = Day25::parse("out 0\nout 1\nout 0\nout 1");
+        assert!(!is_clock_signal(&halting, 1, 100));
+        assert_eq!(Some(3), find_clock_input(&program, 10, 100));
+        assert_eq!(None, find_clock_input(&program, 2, 100));
+        assert_eq!(None, find_clock_input(&halting, 1000, 100));
     }
-}
\ No newline at end of file
+}
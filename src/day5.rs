@@ -1,4 +1,4 @@
-use crate::day::AocDay;
+use crate::{day::AocDay, utils::hashing::{Nibbles, NonceSearch}};
 
 pub struct Day5;
 
@@ -15,45 +15,25 @@ impl AocDay for Day5 {
     }
 
     fn part1(input: &Self::I) -> Self::O {
-        (0..u32::MAX)
-            .map(|i| {format!("{input}{i}")})
-            .filter_map(|text| { 
-                let hash = md5::compute(text);
-                let hash_text = format!("{hash:x}");
-                if hash_text.starts_with("00000")  {
-                    hash_text.chars().nth(5)
-                } else {
-                    None
-                }
-            })
-            .take(8)
+        NonceSearch::new(input)
+            .find(8, |hash| hash.leading_zero_nibbles(5))
+            .into_iter()
+            .map(|(_, hash)| hash.hex_char(5))
             .collect()
     }
 
     fn part2(input: &Self::I) -> Self::O {
         let mut password: [Option<char>; 8] = [None; 8];
-        for i in 0..u32::MAX {
-            let text = format!("{input}{i}");
-            let hash = md5::compute(text);
-            let hash_text = format!("{hash:x}");
-            if !hash_text.starts_with("00000") { continue; }
-            let sixth = hash_text.chars().nth(5);
-            let seventh = hash_text.chars().nth(6);
-            match (sixth, seventh) {
-                (Some(sixth), Some(seventh)) => {
-                    match sixth.to_digit(10) {
-                        Some(digit) if digit < 8 => {
-                            if password[digit as usize].is_none() {
-                                password[digit as usize] = Some(seventh);
-                                if !password.contains(&None) {
-                                    break;
-                                }
-                            }
-                        }
-                        _ => (),
-                    }
+        let mut search = NonceSearch::new(input);
+        while password.contains(&None) {
+            // Every missing position needs at least one more hit
+            let missing = password.iter().filter(|c| c.is_none()).count();
+            let hits = search.find(missing, |hash| hash.leading_zero_nibbles(5) && hash.nibble(5) < 8);
+            for (_, hash) in hits {
+                let position = hash.nibble(5) as usize;
+                if password[position].is_none() {
+                    password[position] = Some(hash.hex_char(6));
                 }
-                _ => (),
             }
         }
         password.into_iter().map(|x| x.unwrap()).collect()
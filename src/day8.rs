@@ -2,7 +2,7 @@ use std::sync::LazyLock;
 
 use fancy_regex::Regex;
 
-use crate::day::AocDay;
+use crate::{day::AocDay, utils::{error::ParseError, grid::Grid, regex::LinePattern}};
 
 pub enum Operation {
     Rect{ rows: usize, cols: usize },
@@ -15,23 +15,17 @@ static ROTATE_ROW_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"rota
 static ROTATE_COLUMN_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"rotate column x=(\d+) by (\d+)"#).unwrap());
 
 impl Operation {
-    fn parse(input: &str) -> Result<Operation, String> {
-        if let Some(cap) = RECT_PATTERN.captures(input).unwrap() {
-            let cols = cap.get(1).unwrap().as_str().parse().unwrap();
-            let rows = cap.get(2).unwrap().as_str().parse().unwrap();
+    fn parse(input: &str) -> Result<Operation, ParseError> {
+        if let Some((cols, rows)) = RECT_PATTERN.parse_captures(input)? {
             return Ok(Operation::Rect { rows, cols })
         }
-        if let Some(cap) = ROTATE_ROW_PATTERN.captures(input).unwrap() {
-            let row = cap.get(1).unwrap().as_str().parse().unwrap();
-            let amount = cap.get(2).unwrap().as_str().parse().unwrap();
+        if let Some((row, amount)) = ROTATE_ROW_PATTERN.parse_captures(input)? {
             return Ok(Operation::RotateRow { row, amount })
         }
-        if let Some(cap) = ROTATE_COLUMN_PATTERN.captures(input).unwrap() {
-            let col = cap.get(1).unwrap().as_str().parse().unwrap();
-            let amount = cap.get(2).unwrap().as_str().parse().unwrap();
+        if let Some((col, amount)) = ROTATE_COLUMN_PATTERN.parse_captures(input)? {
             return Ok(Operation::RotateCol { col, amount })
         }
-        return Err(input.to_owned())
+        return Err(ParseError::new(format!("Unknown operation \"{input}\"")))
     }    
 }
 
@@ -41,35 +35,13 @@ const HEIGHT: usize = 6;
 
 pub struct Day8;
 
-fn run_instructions(input: &<Day8 as AocDay>::I) -> [[bool; 50]; 6] {
-    let mut grid = [[false; WIDTH]; HEIGHT];
+fn run_instructions(input: &<Day8 as AocDay>::I, width: usize, height: usize) -> Grid<bool> {
+    let mut grid = Grid::empty(width, height);
     for operation in input {
         match operation {
-            Operation::Rect { rows, cols } => {
-                for r in 0..*rows {
-                    for c in 0..*cols {
-                        grid[r][c] = true;
-                    }
-                }
-            },
-            Operation::RotateRow { row, amount } => {
-                let mut new_row = [false; WIDTH];
-                for i in 0..WIDTH {
-                    new_row[(i + amount) % WIDTH] = grid[*row][i];
-                }
-                for i in 0..WIDTH {
-                    grid[*row][i] = new_row[i];
-                }
-            },
-            Operation::RotateCol { col, amount } => {
-                let mut new_col = [false; HEIGHT];
-                for i in 0..HEIGHT {
-                    new_col[(i + amount) % HEIGHT] = grid[i][*col];
-                }
-                for i in 0..HEIGHT {
-                    grid[i][*col] = new_col[i];
-                }
-            },
+            Operation::Rect { rows, cols } => grid.fill_rect(0, 0, *cols, *rows, true),
+            Operation::RotateRow { row, amount } => grid.rotate_row(*row, *amount),
+            Operation::RotateCol { col, amount } => grid.rotate_col(*col, *amount),
         }
     }
     grid
@@ -91,17 +63,13 @@ impl AocDay for Day8 {
     }
 
     fn part1(input: &Self::I) -> Self::O {
-        let grid = run_instructions(input);
-        grid.into_iter()
-            .map(|row| {
-                row.into_iter().filter(|cell| *cell).count()
-            })
-            .sum()
+        let grid = run_instructions(input, WIDTH, HEIGHT);
+        grid.iter().filter(|cell| **cell).count()
     }
 
     fn part2(input: &Self::I) -> Self::O {
-        let grid = run_instructions(input);
-        for row in grid {
+        let grid = run_instructions(input, WIDTH, HEIGHT);
+        for row in grid.rows() {
             let line: String = row.iter().map(|c| if *c {'#'} else {'.'}).collect();
             println!("{line}");
         }
@@ -111,5 +79,20 @@ impl AocDay for Day8 {
 
 #[cfg(test)]
 mod tests {
-    use crate::{day::AocDay, day8::Day8};
+    use crate::{day::AocDay, day8::{Day8, run_instructions}};
+
+    #[test]
+    fn test_part1() {
+        let parsed = Day8::parse(r#"rect 3x2
+rotate column x=1 by 1
+rotate row y=0 by 4
+rotate column x=1 by 1"#);
+        let grid = run_instructions(&parsed, 7, 3);
+        let lines: Vec<String> = grid.rows().map(|row| {
+            row.iter().map(|c| if *c {'#'} else {'.'}).collect()
+        }).collect();
+        assert_eq!(vec![".#..#.#", "#.#....", ".#....."], lines);
+        // Rotations only move pixels around, so the full-size screen lights the same 6
+        assert_eq!(6, Day8::part1(&parsed));
+    }
 }
\ No newline at end of file
@@ -2,7 +2,7 @@ use std::sync::LazyLock;
 
 use regex::Regex;
 
-use crate::day::AocDay;
+use crate::{day::AocDay, utils::regex::CaptureGroups};
 
 pub struct Day9;
 
@@ -11,8 +11,7 @@ static PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\((\d+)x(\d+)\)
 fn parse_pattern(input: &str) -> (usize, usize, usize) {
     let capture = PATTERN.captures(input).unwrap();
     let m = capture.get_match();
-    let size: usize = capture.get(1).unwrap().as_str().parse().unwrap();
-    let amount = capture.get(2).unwrap().as_str().parse().unwrap();
+    let (size, amount): (usize, usize) = capture.parse().unwrap();
     let start = m.end();
     let end = start + size;
     (amount, start, end)
@@ -52,8 +51,7 @@ impl AocDay for Day9 {
             let start = m.start();
             if start < head { continue; }
             total += start - head;
-            let size: usize = capture.get(1).unwrap().as_str().parse().unwrap();
-            let amount: usize = capture.get(2).unwrap().as_str().parse().unwrap();
+            let (size, amount): (usize, usize) = capture.parse().unwrap();
             total += size * amount;
             head = m.end() + size;
         }
@@ -1,5 +1,6 @@
 
-mod utils;
+pub mod utils;
+pub mod assembunny;
 
 pub mod day;
 pub mod day1;
@@ -1,17 +1,57 @@
+use crate::utils::point::{Point, YAxis};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Direction {
     North,
     East,
     South,
-    West
+    West,
+    NorthEast,
+    SouthEast,
+    SouthWest,
+    NorthWest,
 }
 
 impl Direction {
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// Parses `U`/`D`/`L`/`R` as well as `N`/`E`/`S`/`W`, where up is north.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'U' | 'N' => Some(Direction::North),
+            'R' | 'E' => Some(Direction::East),
+            'D' | 'S' => Some(Direction::South),
+            'L' | 'W' => Some(Direction::West),
+            _ => None,
+        }
+    }
+
     pub fn left(&self) -> Self {
         return match self  {
             Direction::North => Direction::West,
             Direction::East => Direction::North,
             Direction::South => Direction::East,
             Direction::West => Direction::South,
+            Direction::NorthEast => Direction::NorthWest,
+            Direction::SouthEast => Direction::NorthEast,
+            Direction::SouthWest => Direction::SouthEast,
+            Direction::NorthWest => Direction::SouthWest,
         }
     }
 
@@ -21,15 +61,61 @@ impl Direction {
             Direction::East => Direction::South,
             Direction::South => Direction::West,
             Direction::West => Direction::North,
+            Direction::NorthEast => Direction::SouthEast,
+            Direction::SouthEast => Direction::SouthWest,
+            Direction::SouthWest => Direction::NorthWest,
+            Direction::NorthWest => Direction::NorthEast,
         }
     }
 
+    pub fn turn_around(&self) -> Self {
+        self.left().left()
+    }
+
+    /// The unit step in this direction, with north as +y.
     pub fn offset(&self) -> (i32, i32) {
         return match self {
             Direction::North => (0, 1),
             Direction::East => (1, 0),
             Direction::South => (0, -1),
             Direction::West => (-1, 0),
+            Direction::NorthEast => (1, 1),
+            Direction::SouthEast => (1, -1),
+            Direction::SouthWest => (-1, -1),
+            Direction::NorthWest => (-1, 1),
         }
     }
-}
\ No newline at end of file
+
+    /// The unit step in this direction, with north pointing along `y_axis`.
+    pub fn to_point(self, y_axis: YAxis) -> Point {
+        let (x, y) = self.offset();
+        match y_axis {
+            YAxis::Up => Point::new(x as i64, y as i64),
+            YAxis::Down => Point::new(x as i64, -y as i64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{direction::Direction, point::{Point, YAxis}};
+
+    #[test]
+    fn test_turns() {
+        for direction in Direction::ALL {
+            assert_eq!(direction, direction.left().right());
+            assert_eq!(direction, direction.turn_around().turn_around());
+            assert_eq!(Point::new(0, 0), direction.to_point(YAxis::Up) + direction.turn_around().to_point(YAxis::Up));
+        }
+        assert_eq!(Direction::SouthWest, Direction::NorthEast.turn_around());
+    }
+
+    #[test]
+    fn test_conventions() {
+        let up = Direction::from_char('U').unwrap();
+        assert_eq!(Point::new(0, 1), up.to_point(YAxis::Up));
+        assert_eq!(Point::new(0, -1), up.to_point(YAxis::Down));
+        assert_eq!(Some(Direction::West), Direction::from_char('W'));
+        assert_eq!(None, Direction::from_char('x'));
+    }
+}
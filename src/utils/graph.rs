@@ -0,0 +1,177 @@
+use std::{collections::{HashMap, HashSet}, fmt::Write, hash::Hash};
+
+use crate::utils::{grid::Grid, search::dijkstra_all};
+
+/// An undirected graph with a distance on every edge.
+pub struct WeightedGraph<N> {
+    edges: HashMap<N, Vec<(N, u32)>>,
+}
+
+impl<N : Copy + Hash + Eq> Default for WeightedGraph<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WeightedGraph<(usize, usize)> {
+    /// Connects every passable cell of `grid` to its passable direct neighbours, with distance 1.
+    pub fn from_grid<T>(grid: &Grid<T>, passable: impl Fn(&T) -> bool) -> Self {
+        let mut graph = Self::new();
+        for (x, y, value) in grid.iter_coords() {
+            if !passable(value) { continue; }
+            let neighbours = grid.direct_neighbours(x, y)
+                .filter(|(_, _, neighbour)| passable(neighbour))
+                .map(|(nx, ny, _)| ((nx, ny), 1))
+                .collect();
+            graph.edges.insert((x, y), neighbours);
+        }
+        graph
+    }
+}
+
+impl<N : Copy + Hash + Eq> WeightedGraph<N> {
+    pub fn new() -> Self {
+        Self { edges: HashMap::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    pub fn contains(&self, node: &N) -> bool {
+        self.edges.contains_key(node)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item=&N> {
+        self.edges.keys()
+    }
+
+    pub fn neighbours(&self, node: &N) -> &[(N, u32)] {
+        self.edges.get(node).map_or(&[], |neighbours| neighbours.as_slice())
+    }
+
+    /// Every edge once, as `(a, b, distance)`.
+    pub fn edges(&self) -> impl Iterator<Item=(N, N, u32)> {
+        let mut seen = HashSet::new();
+        self.edges.iter().flat_map(|(a, neighbours)| {
+            neighbours.iter().map(move |(b, distance)| (*a, *b, *distance))
+        }).filter(move |(a, b, _)| {
+            seen.insert((*a, *b));
+            a == b || !seen.contains(&(*b, *a))
+        })
+    }
+
+    fn set_directed(&mut self, from: N, to: N, distance: u32) {
+        let neighbours = self.edges.entry(from).or_default();
+        match neighbours.iter_mut().find(|(node, _)| *node == to) {
+            Some(edge) => edge.1 = edge.1.min(distance),
+            None => neighbours.push((to, distance)),
+        }
+    }
+
+    /// Connects `a` and `b`. When they already are connected, the shortest distance is kept.
+    pub fn add_edge(&mut self, a: N, b: N, distance: u32) {
+        self.set_directed(a, b, distance);
+        self.set_directed(b, a, distance);
+    }
+
+    pub fn remove_node(&mut self, node: &N) -> Vec<(N, u32)> {
+        let neighbours = self.edges.remove(node).unwrap_or_default();
+        for (neighbour, _) in &neighbours {
+            if let Some(edges) = self.edges.get_mut(neighbour) {
+                edges.retain(|(other, _)| other != node);
+            }
+        }
+        neighbours
+    }
+
+    /// Removes every node that is not kept and only forms part of a corridor.
+    /// Dead ends are dropped, and nodes with two neighbours are replaced by a single longer edge,
+    /// so distances between the remaining nodes stay the same.
+    pub fn contract(&mut self, keep: impl Fn(&N) -> bool) {
+        let mut pending: Vec<_> = self.edges.keys().copied().collect();
+        while let Some(node) = pending.pop() {
+            if keep(&node) { continue; }
+            let degree = match self.edges.get(&node) {
+                Some(neighbours) => neighbours.len(),
+                None => continue,
+            };
+            match degree {
+                0 | 1 => {
+                    let neighbours = self.remove_node(&node);
+                    pending.extend(neighbours.into_iter().map(|(neighbour, _)| neighbour));
+                },
+                2 => {
+                    let neighbours = self.remove_node(&node);
+                    let (a, to_a) = neighbours[0];
+                    let (b, to_b) = neighbours[1];
+                    self.add_edge(a, b, to_a + to_b);
+                    pending.push(a);
+                    pending.push(b);
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// The shortest distance from `start` to every node reachable from it.
+    pub fn distances_from(&self, start: N) -> HashMap<N, u32> {
+        dijkstra_all(start, |node| self.neighbours(node).iter().copied())
+    }
+
+    /// The matrix of shortest distances between every pair of `points`, in the same order.
+    /// Points that cannot reach each other are `u32::MAX` apart.
+    pub fn all_pairs(&self, points: &[N]) -> Vec<Vec<u32>> {
+        points.iter().map(|from| {
+            let distances = self.distances_from(*from);
+            points.iter().map(|to| distances.get(to).copied().unwrap_or(u32::MAX)).collect()
+        }).collect()
+    }
+
+}
+
+impl<N : Copy + Hash + Ord> WeightedGraph<N> {
+    /// Writes the graph in Graphviz dot format, naming every node with `label`.
+    pub fn to_dot(&self, label: impl Fn(&N) -> String) -> String {
+        let mut edges: Vec<_> = self.edges()
+            .map(|(a, b, distance)| (a.min(b), a.max(b), distance))
+            .collect();
+        edges.sort();
+        let mut dot = String::from("graph {\n");
+        for (a, b, distance) in edges {
+            writeln!(dot, "    \"{}\" -- \"{}\" [label={distance}];", label(&a), label(&b)).unwrap();
+        }
+        dot.push('}');
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{graph::WeightedGraph, grid::Grid};
+
+    #[test]
+    fn test_contract() {
+        let grid = Grid::from_chars("#######\n#a...b#\n#.###.#\n#...#c#\n#######", |c| c);
+        let mut graph = WeightedGraph::from_grid(&grid, |c| *c != '#');
+        assert_eq!(11, graph.len());
+        graph.contract(|(x, y)| grid[(*x, *y)].is_alphabetic());
+        assert_eq!(3, graph.len());
+        assert_eq!(2, graph.edges().count());
+
+        let points = [(1, 1), (5, 1), (5, 3)];
+        assert_eq!(vec![vec![0, 4, 6], vec![4, 0, 2], vec![6, 2, 0]], graph.all_pairs(&points));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut graph = WeightedGraph::new();
+        graph.add_edge('a', 'b', 3);
+        graph.add_edge('a', 'b', 2);
+        assert_eq!("graph {\n    \"a\" -- \"b\" [label=2];\n}", graph.to_dot(|c| c.to_string()));
+    }
+}
@@ -1,3 +1,11 @@
+use std::ops::{Index, IndexMut};
+
+const DIRECT_OFFSETS: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const ALL_OFFSETS: [(isize, isize); 8] = [
+    (0, -1), (1, -1), (1, 0), (1, 1),
+    (0, 1), (-1, 1), (-1, 0), (-1, -1),
+];
+
 pub struct Grid<T> {
     pub width: usize,
     pub height: usize,
@@ -15,6 +23,17 @@ impl<T : Default + Copy> Grid<T> {
 }
 
 impl<T> Grid<T> {
+    /// Builds a grid from lines of text, converting every character with `f`.
+    pub fn from_chars(contents: &str, f: impl Fn(char) -> T) -> Self {
+        let lines: Vec<_> = contents.lines().collect();
+        let width = lines.first().map_or(0, |line| line.chars().count());
+        let contents: Vec<_> = lines.iter().flat_map(|line| line.chars().map(&f)).collect();
+        if contents.len() != width * lines.len() {
+            panic!("Lines of a grid should all have length {width}");
+        }
+        Self { width, height: lines.len(), contents }
+    }
+
     pub fn in_bounds(&self, x: usize, y: usize) -> bool {
         return x < self.width && y < self.height
     }
@@ -31,42 +50,308 @@ impl<T> Grid<T> {
         return &self.contents[y * self.width + x]
     }
 
-    pub fn set(&mut self, x: usize, y: usize, value: T) -> bool {
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
         if !self.in_bounds(x, y) {
-            panic!("Index ({x} {y}) out of bounds for grid of size ({} {})", self.width, self.height);
+            None
+        } else {
+            Some(&mut self.contents[y * self.width + x])
         }
-        self.contents[y*self.width + x] = value;
-        true
-    }
-
-    pub fn direct_neighbours(&self, x: usize, y: usize) -> Vec<(usize, usize, &T)> {
-        let mut neighbours = Vec::new();
-        if self.in_bounds(x, y) {
-            if y > 0 {
-                neighbours.push((x, y - 1, self.get_unchecked(x, y - 1)));
-            }
-            if x < self.width - 1 {
-                neighbours.push((x + 1, y, self.get_unchecked(x + 1, y)));
-            }
-            if y < self.height - 1 {
-                neighbours.push((x, y + 1, self.get_unchecked(x, y + 1)));
-            }
-            if x > 0 {
-                neighbours.push((x - 1, y, self.get_unchecked(x - 1, y)));
-            }
+    }
+
+    /// Replaces the value at `(x, y)`, returning the previous one.
+    /// Panics when the coordinate is out of bounds.
+    pub fn set(&mut self, x: usize, y: usize, value: T) -> T {
+        std::mem::replace(&mut self[(x, y)], value)
+    }
+
+    fn offset(&self, x: usize, y: usize, (dx, dy): (isize, isize)) -> Option<(usize, usize)> {
+        let nx = x.checked_add_signed(dx)?;
+        let ny = y.checked_add_signed(dy)?;
+        if self.in_bounds(nx, ny) {
+            Some((nx, ny))
+        } else {
+            None
         }
-        neighbours
+    }
+
+    fn wrapping_offset(&self, x: usize, y: usize, (dx, dy): (isize, isize)) -> (usize, usize) {
+        let nx = (x as isize + dx).rem_euclid(self.width as isize) as usize;
+        let ny = (y as isize + dy).rem_euclid(self.height as isize) as usize;
+        (nx, ny)
+    }
+
+    fn neighbours_with<'a>(
+        &'a self,
+        x: usize,
+        y: usize,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item=(usize, usize, &'a T)> {
+        let origin_in_bounds = self.in_bounds(x, y);
+        offsets.iter()
+            .filter(move |_| origin_in_bounds)
+            .filter_map(move |offset| self.offset(x, y, *offset))
+            .map(|(nx, ny)| (nx, ny, self.get_unchecked(nx, ny)))
+    }
+
+    /// The up to 4 orthogonal neighbours of `(x, y)`, in the order up, right, down, left.
+    pub fn direct_neighbours(&self, x: usize, y: usize) -> impl Iterator<Item=(usize, usize, &T)> {
+        self.neighbours_with(x, y, &DIRECT_OFFSETS)
+    }
+
+    /// The up to 8 neighbours of `(x, y)`, clockwise starting from up.
+    pub fn all_neighbours(&self, x: usize, y: usize) -> impl Iterator<Item=(usize, usize, &T)> {
+        self.neighbours_with(x, y, &ALL_OFFSETS)
+    }
+
+    fn wrapping_neighbours_with<'a>(
+        &'a self,
+        x: usize,
+        y: usize,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item=(usize, usize, &'a T)> {
+        // An empty grid has nothing to wrap around to
+        let is_empty = self.width == 0 || self.height == 0;
+        offsets.iter()
+            .filter(move |_| !is_empty)
+            .map(move |offset| self.wrapping_offset(x, y, *offset))
+            .map(|(nx, ny)| (nx, ny, self.get_unchecked(nx, ny)))
+    }
+
+    /// The 4 orthogonal neighbours of `(x, y)`, wrapping around the edges of the grid.
+    pub fn wrapping_neighbours(&self, x: usize, y: usize) -> impl Iterator<Item=(usize, usize, &T)> {
+        self.wrapping_neighbours_with(x, y, &DIRECT_OFFSETS)
+    }
+
+    /// The 8 neighbours of `(x, y)`, wrapping around the edges of the grid.
+    pub fn wrapping_all_neighbours(&self, x: usize, y: usize) -> impl Iterator<Item=(usize, usize, &T)> {
+        self.wrapping_neighbours_with(x, y, &ALL_OFFSETS)
     }
 
     pub fn iter(&self) -> impl Iterator<Item=&T> {
         self.contents.iter()
     }
 
+    pub fn iter_mut(&mut self) -> impl Iterator<Item=&mut T> {
+        self.contents.iter_mut()
+    }
+
+    /// Iterates over all cells in row-major order as `(x, y, value)`.
+    pub fn iter_coords(&self) -> impl Iterator<Item=(usize, usize, &T)> {
+        let width = self.width;
+        self.contents.iter().enumerate().map(move |(i, value)| {
+            (i % width, i / width, value)
+        })
+    }
+
     pub fn row(&self, y: usize) -> Option<&[T]> {
-        if y >= self.width {
+        if y >= self.height {
             None
         } else {
             Some(&self.contents[y*self.width..(y+1)*self.width])
         }
     }
-}
\ No newline at end of file
+
+    pub fn rows(&self) -> impl Iterator<Item=&[T]> {
+        self.contents.chunks(self.width.max(1)).take(self.height)
+    }
+
+    pub fn column(&self, x: usize) -> Option<impl Iterator<Item=&T>> {
+        if x >= self.width {
+            None
+        } else {
+            Some(self.contents.iter().skip(x).step_by(self.width))
+        }
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item=impl Iterator<Item=&T>> {
+        (0..self.width).map(|x| self.contents.iter().skip(x).step_by(self.width))
+    }
+}
+
+impl<T : Clone> Grid<T> {
+    /// Sets every cell in the `width` by `height` rectangle with its top left corner at `(x, y)`.
+    /// Parts of the rectangle that fall outside of the grid are ignored.
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, value: T) {
+        let x_end = x.saturating_add(width).min(self.width);
+        let y_end = y.saturating_add(height).min(self.height);
+        for row in y.min(y_end)..y_end {
+            self.contents[row*self.width + x.min(x_end)..row*self.width + x_end].fill(value.clone());
+        }
+    }
+
+    /// Swaps rows and columns, so the grid of size (w h) becomes one of size (h w).
+    pub fn transpose(&mut self) {
+        let contents = (0..self.width).flat_map(|x| {
+            (0..self.height).map(move |y| (x, y))
+        }).map(|(x, y)| self.get_unchecked(x, y).clone()).collect();
+        self.contents = contents;
+        std::mem::swap(&mut self.width, &mut self.height);
+    }
+
+    /// Rotates the whole grid a quarter turn clockwise.
+    pub fn rotate90(&mut self) {
+        self.transpose();
+        self.flip_h();
+    }
+}
+
+impl<T> Grid<T> {
+    fn reverse_indices(&mut self, indices: &[usize]) {
+        let (mut start, mut end) = (0, indices.len());
+        while start + 1 < end {
+            end -= 1;
+            self.contents.swap(indices[start], indices[end]);
+            start += 1;
+        }
+    }
+
+    /// Shifts row `y` to the right by `amount`, wrapping around the right edge.
+    pub fn rotate_row(&mut self, y: usize, amount: usize) {
+        if y >= self.height || self.width == 0 {
+            panic!("Row {y} out of bounds for grid of size ({} {})", self.width, self.height);
+        }
+        self.contents[y*self.width..(y+1)*self.width].rotate_right(amount % self.width);
+    }
+
+    /// Shifts column `x` down by `amount`, wrapping around the bottom edge.
+    pub fn rotate_col(&mut self, x: usize, amount: usize) {
+        if x >= self.width || self.height == 0 {
+            panic!("Column {x} out of bounds for grid of size ({} {})", self.width, self.height);
+        }
+        let amount = amount % self.height;
+        let indices: Vec<_> = (0..self.height).map(|y| y*self.width + x).collect();
+        // Rotating right is reversing the whole column, then both parts separately
+        self.reverse_indices(&indices);
+        self.reverse_indices(&indices[..amount]);
+        self.reverse_indices(&indices[amount..]);
+    }
+
+    /// Mirrors the grid left to right.
+    pub fn flip_h(&mut self) {
+        for y in 0..self.height {
+            self.contents[y*self.width..(y+1)*self.width].reverse();
+        }
+    }
+
+    /// Mirrors the grid top to bottom.
+    pub fn flip_v(&mut self) {
+        for y in 0..self.height / 2 {
+            let other = self.height - 1 - y;
+            let (top, bottom) = self.contents.split_at_mut(other*self.width);
+            top[y*self.width..(y+1)*self.width].swap_with_slice(&mut bottom[..self.width]);
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        self.get(x, y).unwrap_or_else(|| {
+            panic!("Index ({x} {y}) out of bounds for grid of size ({} {})", self.width, self.height)
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(x, y).unwrap_or_else(|| {
+            panic!("Index ({x} {y}) out of bounds for grid of size ({width} {height})")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::grid::Grid;
+
+    fn numbered(width: usize, height: usize) -> Grid<usize> {
+        Grid { width, height, contents: (0..width*height).collect() }
+    }
+
+    #[test]
+    fn test_index() {
+        let mut grid = numbered(3, 2);
+        assert_eq!(5, grid[(2, 1)]);
+        grid[(2, 1)] = 10;
+        assert_eq!(Some(&10), grid.get(2, 1));
+        assert_eq!(10, grid.set(2, 1, 11));
+        assert_eq!(None, grid.get_mut(3, 0));
+    }
+
+    #[test]
+    fn test_from_chars() {
+        let grid = Grid::from_chars("#.\n.#", |c| c == '#');
+        assert_eq!((2, 2), (grid.width, grid.height));
+        assert_eq!(vec![true, false, false, true], grid.contents);
+    }
+
+    #[test]
+    fn test_iter_coords() {
+        let grid = numbered(3, 2);
+        let coords: Vec<_> = grid.iter_coords().map(|(x, y, v)| (x, y, *v)).collect();
+        assert_eq!(vec![(0, 0, 0), (1, 0, 1), (2, 0, 2), (0, 1, 3), (1, 1, 4), (2, 1, 5)], coords);
+    }
+
+    #[test]
+    fn test_columns() {
+        let grid = numbered(3, 2);
+        let columns: Vec<Vec<_>> = grid.columns().map(|col| col.copied().collect()).collect();
+        assert_eq!(vec![vec![0, 3], vec![1, 4], vec![2, 5]], columns);
+        assert_eq!(Some(&[3, 4, 5][..]), grid.row(1));
+        assert!(grid.row(2).is_none());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = numbered(3, 3);
+        let direct: Vec<_> = grid.direct_neighbours(0, 0).map(|(_, _, v)| *v).collect();
+        assert_eq!(vec![1, 3], direct);
+        assert_eq!(8, grid.all_neighbours(1, 1).count());
+        assert_eq!(3, grid.all_neighbours(0, 0).count());
+        assert_eq!(0, grid.direct_neighbours(3, 0).count());
+        let wrapping: Vec<_> = grid.wrapping_neighbours(0, 0).map(|(_, _, v)| *v).collect();
+        assert_eq!(vec![6, 1, 3, 2], wrapping);
+        assert_eq!(8, grid.wrapping_all_neighbours(0, 0).count());
+
+        let empty: Grid<u32> = Grid::empty(0, 3);
+        assert_eq!(0, empty.wrapping_neighbours(0, 0).count());
+        assert_eq!(0, empty.wrapping_all_neighbours(0, 0).count());
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut grid = numbered(3, 2);
+        grid.rotate_row(0, 1);
+        assert_eq!(vec![2, 0, 1, 3, 4, 5], grid.contents);
+        grid.rotate_col(1, 3);
+        assert_eq!(vec![2, 4, 1, 3, 0, 5], grid.contents);
+    }
+
+    #[test]
+    fn test_fill_rect() {
+        let mut grid: Grid<bool> = Grid::empty(4, 3);
+        grid.fill_rect(2, 1, 5, 1, true);
+        assert_eq!(2, grid.iter().filter(|v| **v).count());
+        assert!(grid[(3, 1)]);
+    }
+
+    #[test]
+    fn test_transformations() {
+        let mut grid = numbered(3, 2);
+        grid.transpose();
+        assert_eq!((2, 3), (grid.width, grid.height));
+        assert_eq!(vec![0, 3, 1, 4, 2, 5], grid.contents);
+
+        let mut grid = numbered(3, 2);
+        grid.rotate90();
+        assert_eq!(vec![3, 0, 4, 1, 5, 2], grid.contents);
+
+        let mut grid = numbered(3, 2);
+        grid.flip_h();
+        assert_eq!(vec![2, 1, 0, 5, 4, 3], grid.contents);
+        grid.flip_v();
+        assert_eq!(vec![5, 4, 3, 2, 1, 0], grid.contents);
+    }
+}
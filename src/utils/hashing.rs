@@ -0,0 +1,259 @@
+use std::io::Write;
+
+pub use md5::Digest;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+/// Hashes `salt || decimal(index)` for many indices, reusing a single input buffer.
+#[derive(Clone, Debug)]
+pub struct SaltedHasher {
+    buffer: Vec<u8>,
+    salt_length: usize,
+}
+
+impl SaltedHasher {
+    pub fn new(salt: &str) -> Self {
+        let mut buffer = Vec::with_capacity(salt.len() + 20);
+        buffer.extend_from_slice(salt.as_bytes());
+        Self { buffer, salt_length: salt.len() }
+    }
+
+    pub fn hash(&mut self, index: u64) -> Digest {
+        self.buffer.truncate(self.salt_length);
+        write!(self.buffer, "{index}").unwrap();
+        md5::compute(&self.buffer)
+    }
+
+    /// Hashes the salt followed by an arbitrary suffix.
+    pub fn hash_suffix(&mut self, suffix: &[u8]) -> Digest {
+        self.buffer.truncate(self.salt_length);
+        self.buffer.extend_from_slice(suffix);
+        md5::compute(&self.buffer)
+    }
+}
+
+/// Parallel search for indices whose salted hash satisfies a predicate.
+/// Indices are hashed in chunks spread over the rayon thread pool, but hits are always reported in index order.
+/// Every search continues where the previous one stopped, so a search can be resumed in steps.
+pub struct NonceSearch<'a> {
+    salt: &'a str,
+    next_index: u64,
+    chunk_size: u64,
+    progress: Option<&'a (dyn Fn(u64) + Sync)>,
+}
+
+impl<'a> NonceSearch<'a> {
+    pub fn new(salt: &'a str) -> Self {
+        Self { salt, next_index: 0, chunk_size: 10_000, progress: None }
+    }
+
+    /// Starts searching at `index` instead of 0.
+    pub fn starting_at(mut self, index: u64) -> Self {
+        self.next_index = index;
+        self
+    }
+
+    /// How many consecutive indices a single thread hashes at a time.
+    pub fn chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Calls `progress` with the next index to search after every batch of chunks.
+    pub fn with_progress(mut self, progress: &'a (dyn Fn(u64) + Sync)) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// The first index that has not been searched yet.
+    pub fn next_index(&self) -> u64 {
+        self.next_index
+    }
+
+    /// Finds the next `count` indices whose hash satisfies `is_hit`, in increasing order.
+    pub fn find(&mut self, count: usize, is_hit: impl Fn(&Digest) -> bool + Sync) -> Vec<(u64, Digest)> {
+        let mut hits: Vec<(u64, Digest)> = Vec::with_capacity(count);
+        let batch_chunks = rayon::current_num_threads() as u64;
+        while hits.len() < count && self.next_index < u64::MAX {
+            let batch_start = self.next_index;
+            let chunks: Vec<Vec<_>> = (0..batch_chunks).into_par_iter().map_init(
+                || SaltedHasher::new(self.salt),
+                |hasher, chunk| {
+                    let start = batch_start.saturating_add(chunk * self.chunk_size);
+                    let end = start.saturating_add(self.chunk_size);
+                    (start..end)
+                        .map(|index| (index, hasher.hash(index)))
+                        .filter(|(_, hash)| is_hit(hash))
+                        .collect()
+                },
+            ).collect();
+
+            self.next_index = batch_start.saturating_add(batch_chunks * self.chunk_size);
+            for hit in chunks.into_iter().flatten() {
+                hits.push(hit);
+                if hits.len() == count {
+                    // Resume right after the last reported hit, so the remaining ones are found again
+                    self.next_index = hit.0 + 1;
+                    break;
+                }
+            }
+            if let Some(progress) = self.progress {
+                progress(self.next_index);
+            }
+        }
+        hits
+    }
+}
+
+/// Hashes the concatenation of `parts` without copying them into one buffer.
+pub fn hash_concat(parts: &[&[u8]]) -> Digest {
+    let mut context = md5::Context::new();
+    for part in parts {
+        context.consume(part);
+    }
+    context.finalize()
+}
+
+/// Hashes the lowercase hex representation of `digest` again, `rounds` times.
+pub fn stretch(mut digest: Digest, rounds: usize) -> Digest {
+    let mut hex = [0; 32];
+    for _ in 0..rounds {
+        for (i, nibble) in digest.nibbles().enumerate() {
+            hex[i] = HEX_DIGITS[nibble as usize];
+        }
+        digest = md5::compute(hex);
+    }
+    digest
+}
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Inspects the hexadecimal digits of a digest without formatting it as a string.
+pub trait Nibbles {
+    /// The `k`th hexadecimal digit, counting from the most significant one.
+    fn nibble(&self, k: usize) -> u8;
+
+    fn nibbles(&self) -> impl Iterator<Item=u8>;
+
+    /// Whether the first `n` hexadecimal digits are all zero.
+    fn leading_zero_nibbles(&self, n: usize) -> bool;
+
+    /// The first digit that is repeated at least `length` times in a row.
+    fn has_run(&self, length: usize) -> Option<u8>;
+
+    /// Whether `nibble` is repeated at least `length` times in a row anywhere.
+    fn contains_run(&self, nibble: u8, length: usize) -> bool;
+
+    /// One bit per door for the first four digits, set when the digit is `b` to `f`.
+    /// The first digit ends up in the least significant bit.
+    fn door_open_bits(&self) -> u8;
+
+    /// The `k`th hexadecimal digit as a lowercase character.
+    fn hex_char(&self, k: usize) -> char {
+        HEX_DIGITS[self.nibble(k) as usize] as char
+    }
+}
+
+impl Nibbles for Digest {
+    fn nibble(&self, k: usize) -> u8 {
+        let byte = self.0[k / 2];
+        if k.is_multiple_of(2) { byte >> 4 } else { byte & 0xf }
+    }
+
+    fn nibbles(&self) -> impl Iterator<Item=u8> {
+        self.0.iter().flat_map(|byte| [byte >> 4, byte & 0xf])
+    }
+
+    fn leading_zero_nibbles(&self, n: usize) -> bool {
+        self.0[..n / 2].iter().all(|byte| *byte == 0) && (n.is_multiple_of(2) || self.0[n / 2] >> 4 == 0)
+    }
+
+    fn has_run(&self, length: usize) -> Option<u8> {
+        let mut current = None;
+        let mut count = 0;
+        for nibble in self.nibbles() {
+            if current == Some(nibble) {
+                count += 1;
+            } else {
+                current = Some(nibble);
+                count = 1;
+            }
+            if count == length {
+                return current;
+            }
+        }
+        None
+    }
+
+    fn contains_run(&self, nibble: u8, length: usize) -> bool {
+        let mut count = 0;
+        for n in self.nibbles() {
+            count = if n == nibble { count + 1 } else { 0 };
+            if count == length {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn door_open_bits(&self) -> u8 {
+        (0..4).filter(|k| self.nibble(*k) >= 0xb).fold(0, |bits, k| bits | 1 << k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use crate::utils::hashing::{Digest, NonceSearch, Nibbles, SaltedHasher, hash_concat, stretch};
+
+    #[test]
+    fn test_salted_hasher() {
+        let mut hasher = SaltedHasher::new("abc");
+        let digest = hasher.hash(3231929);
+        assert_eq!("00000155f8105dff7f56ee10fa9b9abd", format!("{digest:x}"));
+        assert!(digest.leading_zero_nibbles(5));
+        assert!(!digest.leading_zero_nibbles(6));
+        assert_eq!('1', digest.hex_char(5));
+        assert_eq!(5, digest.nibble(7));
+        assert_eq!(digest, hash_concat(&[b"abc", b"3231929"]));
+        assert_eq!(digest, hasher.hash_suffix(b"3231929"));
+    }
+
+    #[test]
+    fn test_runs() {
+        let digest = SaltedHasher::new("abc").hash(18);
+        // 0034e0923cc38887a57bd7b1d4f953df
+        assert_eq!(Some(8), digest.has_run(3));
+        assert_eq!(Some(0), digest.has_run(2));
+        assert!(digest.contains_run(8, 3));
+        assert!(!digest.contains_run(8, 4));
+    }
+
+    #[test]
+    fn test_stretch_and_doors() {
+        let digest = stretch(SaltedHasher::new("abc").hash(0), 2016);
+        assert_eq!("a107ff634856bb300138cac6568c0f24", format!("{digest:x}"));
+        // "ced9" opens up, down and left but not right
+        let doors = hash_concat(&[b"hijkl"]).door_open_bits();
+        assert_eq!(0b0111, doors);
+    }
+
+    #[test]
+    fn test_nonce_search() {
+        let is_hit = |hash: &Digest| hash.leading_zero_nibbles(3);
+        let mut hasher = SaltedHasher::new("abc");
+        let expected: Vec<u64> = (0..).filter(|i| is_hit(&hasher.hash(*i))).take(6).collect();
+
+        let batches = AtomicU64::new(0);
+        let progress = |_| { batches.fetch_add(1, Ordering::Relaxed); };
+        let mut search = NonceSearch::new("abc").chunk_size(1000).with_progress(&progress);
+        let mut found: Vec<u64> = search.find(4, is_hit).into_iter().map(|(index, _)| index).collect();
+        assert_eq!(expected[3] + 1, search.next_index());
+        found.extend(search.find(2, is_hit).into_iter().map(|(index, _)| index));
+        assert_eq!(expected, found);
+        assert!(batches.load(Ordering::Relaxed) > 0);
+
+        let resumed = NonceSearch::new("abc").starting_at(expected[2] + 1).find(1, is_hit);
+        assert_eq!(expected[3], resumed[0].0);
+    }
+}
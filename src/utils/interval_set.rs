@@ -0,0 +1,178 @@
+use num_traits::PrimInt;
+
+/// A set of integers stored as sorted, disjoint and non-adjacent inclusive ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IntervalSet<T> {
+    ranges: Vec<(T, T)>,
+}
+
+impl<T : PrimInt> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The covered ranges in increasing order, as inclusive `(start, end)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item=(T, T)> {
+        self.ranges.iter().copied()
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.ranges.partition_point(|(_, end)| *end < value);
+        self.ranges.get(i).is_some_and(|(start, _)| *start <= value)
+    }
+
+    /// Adds every value from `start` up to and including `end`.
+    pub fn insert(&mut self, start: T, end: T) {
+        if start > end { return; }
+        // Ranges that overlap or touch the new range get merged into it
+        let first = self.ranges.partition_point(|(_, e)| e.saturating_add(T::one()) < start);
+        let last = self.ranges.partition_point(|(s, _)| *s <= end.saturating_add(T::one()));
+        let merged = if first < last {
+            (start.min(self.ranges[first].0), end.max(self.ranges[last - 1].1))
+        } else {
+            (start, end)
+        };
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    /// Removes every value from `start` up to and including `end`.
+    pub fn remove(&mut self, start: T, end: T) {
+        if start > end { return; }
+        let first = self.ranges.partition_point(|(_, e)| *e < start);
+        let last = self.ranges.partition_point(|(s, _)| *s <= end);
+        if first >= last { return; }
+        let (first_start, _) = self.ranges[first];
+        let (_, last_end) = self.ranges[last - 1];
+        let mut remaining = Vec::with_capacity(2);
+        if first_start < start {
+            remaining.push((first_start, start - T::one()));
+        }
+        if last_end > end {
+            remaining.push((end + T::one(), last_end));
+        }
+        self.ranges.splice(first..last, remaining);
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = self.clone();
+        for (start, end) in other.iter() {
+            result.insert(start, end);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a_start, a_end) = self.ranges[i];
+            let (b_start, b_end) = other.ranges[j];
+            let (start, end) = (a_start.max(b_start), a_end.min(b_end));
+            if start <= end {
+                ranges.push((start, end));
+            }
+            if a_end < b_end { i += 1; } else { j += 1; }
+        }
+        IntervalSet { ranges }
+    }
+
+    /// The uncovered ranges between `min` and `max` inclusive, in increasing order.
+    pub fn gaps(&self, min: T, max: T) -> impl Iterator<Item=(T, T)> {
+        // The first value that has not been accounted for yet, None once max has been passed
+        let mut next = (min <= max).then_some(min);
+        let first = self.ranges.partition_point(|(_, end)| *end < min);
+        self.ranges[first..].iter()
+            .map(|(start, end)| (Some(*start), *end))
+            .chain(std::iter::once((None, max)))
+            .map_while(move |(start, end)| {
+                let from = next?;
+                let gap_end = match start {
+                    Some(start) if start > max => max,
+                    Some(start) => start.saturating_sub(T::one()).min(max),
+                    None => max,
+                };
+                next = if end >= max { None } else { end.checked_add(&T::one()) };
+                let in_gap = start.is_none_or(|start| start > from);
+                Some((in_gap && gap_end >= from).then_some((from, gap_end)))
+            })
+            .flatten()
+    }
+
+    /// Every value between `min` and `max` inclusive that is not in the set.
+    pub fn complement(&self, min: T, max: T) -> IntervalSet<T> {
+        IntervalSet { ranges: self.gaps(min, max).collect() }
+    }
+
+    /// The lowest value between `min` and `max` inclusive that is not in the set.
+    pub fn first_gap(&self, min: T, max: T) -> Option<T> {
+        self.gaps(min, max).next().map(|(start, _)| start)
+    }
+
+    /// How many values between `min` and `max` inclusive are not in the set.
+    pub fn count_uncovered(&self, min: T, max: T) -> u128 {
+        self.gaps(min, max).map(|(start, end)| {
+            (end.to_i128().unwrap() - start.to_i128().unwrap()) as u128 + 1
+        }).sum()
+    }
+}
+
+impl<T : PrimInt> FromIterator<(T, T)> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        for (start, end) in iter {
+            set.insert(start, end);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::interval_set::IntervalSet;
+
+    #[test]
+    fn test_insert() {
+        let set: IntervalSet<u8> = [(5, 8), (0, 2), (4, 7), (10, 10), (250, 255)].into_iter().collect();
+        assert_eq!(vec![(0, 2), (4, 8), (10, 10), (250, 255)], set.iter().collect::<Vec<_>>());
+        let set = set.union(&[(3, 3), (9, 9)].into_iter().collect());
+        assert_eq!(vec![(0, 10), (250, 255)], set.iter().collect::<Vec<_>>());
+        assert!(set.contains(255));
+        assert!(!set.contains(11));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set: IntervalSet<i32> = [(-10, 10), (20, 30)].into_iter().collect();
+        set.remove(0, 25);
+        assert_eq!(vec![(-10, -1), (26, 30)], set.iter().collect::<Vec<_>>());
+        set.remove(-100, -5);
+        assert_eq!(vec![(-4, -1), (26, 30)], set.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a: IntervalSet<u32> = [(0, 5), (10, 20)].into_iter().collect();
+        let b: IntervalSet<u32> = [(3, 12), (18, 30)].into_iter().collect();
+        assert_eq!(vec![(3, 5), (10, 12), (18, 20)], a.intersection(&b).iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_gaps() {
+        let set: IntervalSet<u32> = [(5, 8), (0, 2), (4, 7)].into_iter().collect();
+        assert_eq!(vec![(3, 3), (9, 9)], set.gaps(0, 9).collect::<Vec<_>>());
+        assert_eq!(Some(3), set.first_gap(0, 9));
+        assert_eq!(Some(9), set.first_gap(4, 20));
+        assert_eq!(1 + 12, set.count_uncovered(0, 20));
+        assert_eq!(u32::MAX as u128 - 7, set.count_uncovered(0, u32::MAX));
+        assert_eq!(vec![(3, 3), (9, 10)], set.complement(1, 10).iter().collect::<Vec<_>>());
+        assert_eq!(None, set.first_gap(4, 8));
+
+        let full: IntervalSet<u8> = [(0, 255)].into_iter().collect();
+        assert_eq!(None, full.first_gap(0, 255));
+        assert_eq!(0, full.count_uncovered(0, 255));
+    }
+}
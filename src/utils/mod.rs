@@ -1,3 +1,13 @@
 pub mod direction;
+pub mod error;
 pub mod regex;
+pub mod search;
+pub mod graph;
 pub mod grid;
+pub mod parser;
+pub mod point;
+pub mod sparse_grid;
+pub mod tsp;
+pub mod number;
+pub mod interval_set;
+pub mod hashing;
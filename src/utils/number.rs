@@ -0,0 +1,92 @@
+/// Returns `(g, x, y)` such that `a*x + b*y = g = gcd(a, b)`.
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a as i128, b as i128);
+    let (mut old_x, mut x) = (1_i128, 0_i128);
+    let (mut old_y, mut y) = (0_i128, 1_i128);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+    if old_r < 0 {
+        (old_r, old_x, old_y) = (-old_r, -old_x, -old_y);
+    }
+    (old_r as i64, old_x as i64, old_y as i64)
+}
+
+pub fn gcd(a: i64, b: i64) -> i64 {
+    extended_gcd(a, b).0
+}
+
+pub fn lcm(a: i64, b: i64) -> i64 {
+    if a == 0 || b == 0 { 0 } else { (a / gcd(a, b) * b).abs() }
+}
+
+/// The `x` in `0..m` with `a*x ≡ 1 (mod m)`, if `a` and `m` are coprime.
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    if g == 1 {
+        Some(x.rem_euclid(m))
+    } else {
+        None
+    }
+}
+
+/// Solves the system `t ≡ residue (mod modulus)` for every `(residue, modulus)` pair.
+/// The moduli do not have to be coprime. Returns the smallest non-negative `t`
+/// together with the period after which the solutions repeat,
+/// or `None` when the congruences contradict each other or the period doesn't fit in an `i64`.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut t: i128 = 0;
+    let mut period: i128 = 1;
+    for (residue, modulus) in congruences {
+        let (residue, modulus) = (*residue as i128, *modulus as i128);
+        // t + period*k ≡ residue (mod modulus) has a solution for k iff gcd divides the difference
+        let (g, inverse, _) = extended_gcd(period as i64, modulus as i64);
+        let g = g as i128;
+        let difference = residue - t;
+        if difference % g != 0 {
+            return None;
+        }
+        let step = modulus / g;
+        let k = (difference / g * inverse as i128).rem_euclid(step);
+        t += period * k;
+        period *= step;
+        if period > i64::MAX as i128 {
+            return None;
+        }
+        t = t.rem_euclid(period);
+    }
+    Some((t as i64, period as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::number::{crt, extended_gcd, lcm, mod_inverse};
+
+    #[test]
+    fn test_extended_gcd() {
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(2, g);
+        assert_eq!(2, 240 * x + 46 * y);
+        assert_eq!(42, lcm(6, 14));
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(Some(4), mod_inverse(3, 11));
+        assert_eq!(Some(10), mod_inverse(-1, 11));
+        assert_eq!(None, mod_inverse(4, 6));
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(Some((23, 105)), crt(&[(2, 3), (3, 5), (2, 7)]));
+        assert_eq!(Some((10, 12)), crt(&[(4, 6), (2, 4)]));
+        assert_eq!(None, crt(&[(1, 4), (2, 6)]));
+        assert_eq!(Some((0, 1)), crt(&[]));
+        // Coprime moduli around 2^31 need a period of about 2^93
+        assert_eq!(None, crt(&[(1, (1 << 31) - 1), (2, 1 << 31), (3, (1 << 31) + 1)]));
+    }
+}
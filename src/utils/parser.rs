@@ -0,0 +1,224 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::utils::error::ParseError;
+
+/// The remaining text to parse, along with its byte offset in the original input.
+#[derive(Clone, Copy, Debug)]
+pub struct Input<'a> {
+    pub rest: &'a str,
+    pub position: usize,
+}
+
+impl<'a> Input<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self { rest: text, position: 0 }
+    }
+
+    fn advance(self, bytes: usize) -> (&'a str, Input<'a>) {
+        let (taken, rest) = self.rest.split_at(bytes);
+        (taken, Input { rest, position: self.position + bytes })
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::at(self.position, message)
+    }
+}
+
+pub type ParseResult<'a, T> = Result<(T, Input<'a>), ParseError>;
+
+/// Anything that can consume a prefix of the input and produce a value.
+/// Implemented for every `Fn(Input) -> ParseResult`, so plain closures are parsers.
+pub trait Parser<'a, T> {
+    fn parse_from(&self, input: Input<'a>) -> ParseResult<'a, T>;
+
+    fn map<U>(self, f: impl Fn(T) -> U) -> impl Parser<'a, U> where Self: Sized {
+        move |input| {
+            let (value, rest) = self.parse_from(input)?;
+            Ok((f(value), rest))
+        }
+    }
+
+    /// Like `map`, but the conversion may fail. The error points at the start of this parser.
+    fn try_map<U, E: Display>(self, f: impl Fn(T) -> Result<U, E>) -> impl Parser<'a, U> where Self: Sized {
+        move |input: Input<'a>| {
+            let (value, rest) = self.parse_from(input)?;
+            let value = f(value).map_err(|err| input.error(err.to_string()))?;
+            Ok((value, rest))
+        }
+    }
+
+    /// Runs `other` after this parser and keeps both values.
+    fn then<U>(self, other: impl Parser<'a, U>) -> impl Parser<'a, (T, U)> where Self: Sized {
+        move |input| {
+            let (first, rest) = self.parse_from(input)?;
+            let (second, rest) = other.parse_from(rest)?;
+            Ok(((first, second), rest))
+        }
+    }
+
+    /// Runs `other` after this parser and keeps only the value of this parser.
+    fn then_ignore<U>(self, other: impl Parser<'a, U>) -> impl Parser<'a, T> where Self: Sized {
+        self.then(other).map(|(value, _)| value)
+    }
+
+    /// Runs `other` after this parser and keeps only the value of `other`.
+    fn ignore_then<U>(self, other: impl Parser<'a, U>) -> impl Parser<'a, U> where Self: Sized {
+        self.then(other).map(|(_, value)| value)
+    }
+
+    /// Tries `other` from the same position when this parser fails.
+    /// When both fail, the error that got furthest into the input is kept.
+    fn or(self, other: impl Parser<'a, T>) -> impl Parser<'a, T> where Self: Sized {
+        move |input| {
+            match self.parse_from(input) {
+                Ok(result) => Ok(result),
+                Err(first) => other.parse_from(input).map_err(|second| {
+                    if first.position >= second.position { first } else { second }
+                }),
+            }
+        }
+    }
+}
+
+impl<'a, T, F> Parser<'a, T> for F where F: Fn(Input<'a>) -> ParseResult<'a, T> {
+    fn parse_from(&self, input: Input<'a>) -> ParseResult<'a, T> {
+        self(input)
+    }
+}
+
+/// Matches exactly `expected`.
+pub fn literal<'a>(expected: &'static str) -> impl Parser<'a, &'a str> {
+    move |input: Input<'a>| {
+        if input.rest.starts_with(expected) {
+            Ok(input.advance(expected.len()))
+        } else {
+            Err(input.error(format!("Expected \"{expected}\"")))
+        }
+    }
+}
+
+/// Matches the longest prefix whose characters all satisfy `predicate`, which may be empty.
+pub fn take_while<'a>(predicate: impl Fn(char) -> bool) -> impl Parser<'a, &'a str> {
+    move |input: Input<'a>| {
+        let end = input.rest.find(|c| !predicate(c)).unwrap_or(input.rest.len());
+        Ok(input.advance(end))
+    }
+}
+
+/// Matches a single character satisfying `predicate`.
+pub fn one_char<'a>(predicate: impl Fn(char) -> bool) -> impl Parser<'a, char> {
+    move |input: Input<'a>| {
+        match input.rest.chars().next() {
+            Some(c) if predicate(c) => Ok((c, input.advance(c.len_utf8()).1)),
+            Some(c) => Err(input.error(format!("Unexpected character '{c}'"))),
+            None => Err(input.error("Unexpected end of input")),
+        }
+    }
+}
+
+/// Matches one or more spaces or tabs.
+pub fn whitespace<'a>() -> impl Parser<'a, &'a str> {
+    move |input: Input<'a>| {
+        let (spaces, rest) = take_while(|c| c == ' ' || c == '\t').parse_from(input)?;
+        if spaces.is_empty() {
+            Err(input.error("Expected whitespace"))
+        } else {
+            Ok((spaces, rest))
+        }
+    }
+}
+
+/// Matches an optionally negative decimal number.
+pub fn integer<'a, T : FromStr>() -> impl Parser<'a, T> where T::Err: Display {
+    move |input: Input<'a>| {
+        let sign = usize::from(input.rest.starts_with('-'));
+        let digits = input.rest[sign..].find(|c: char| !c.is_ascii_digit()).unwrap_or(input.rest.len() - sign);
+        if digits == 0 {
+            return Err(input.error("Expected a number"));
+        }
+        let (text, rest) = input.advance(sign + digits);
+        let value = text.parse().map_err(|err| input.error(format!("Invalid number \"{text}\": {err}")))?;
+        Ok((value, rest))
+    }
+}
+
+/// Matches a letter or underscore followed by letters, digits or underscores.
+pub fn identifier<'a>() -> impl Parser<'a, &'a str> {
+    move |input: Input<'a>| {
+        one_char(|c| c.is_alphabetic() || c == '_').parse_from(input)
+            .map_err(|_| input.error("Expected an identifier"))?;
+        let length = input.rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(input.rest.len());
+        Ok(input.advance(length))
+    }
+}
+
+/// Matches zero or more `item`s with a `separator` in between.
+pub fn separated<'a, T, S>(item: impl Parser<'a, T>, separator: impl Parser<'a, S>) -> impl Parser<'a, Vec<T>> {
+    move |input: Input<'a>| {
+        let mut values = Vec::new();
+        let mut rest = match item.parse_from(input) {
+            Ok((value, rest)) => {
+                values.push(value);
+                rest
+            },
+            Err(_) => return Ok((values, input)),
+        };
+        while let Ok((_, after_separator)) = separator.parse_from(rest) {
+            let (value, after_item) = item.parse_from(after_separator)?;
+            values.push(value);
+            rest = after_item;
+        }
+        Ok((values, rest))
+    }
+}
+
+/// Runs `parser` on the whole of `text`, failing when anything is left over.
+pub fn parse_all<'a, T>(parser: &impl Parser<'a, T>, text: &'a str) -> Result<T, ParseError> {
+    parse_at(parser, text, 0)
+}
+
+fn parse_at<'a, T>(parser: &impl Parser<'a, T>, text: &'a str, position: usize) -> Result<T, ParseError> {
+    let (value, rest) = parser.parse_from(Input { rest: text, position })?;
+    if rest.rest.is_empty() {
+        Ok(value)
+    } else {
+        Err(rest.error(format!("Unexpected trailing input \"{}\"", rest.rest)))
+    }
+}
+
+/// Runs `parser` on every line of `contents`. Error positions are relative to the start of `contents`.
+pub fn parse_lines<'a, T>(parser: &impl Parser<'a, T>, contents: &'a str) -> Result<Vec<T>, ParseError> {
+    contents.lines().map(|line| {
+        let position = line.as_ptr() as usize - contents.as_ptr() as usize;
+        parse_at(parser, line, position)
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{error::ParseError, parser::{Parser, identifier, integer, literal, parse_all, parse_lines, separated, whitespace}};
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(Ok(-12), parse_all(&integer::<i32>(), "-12"));
+        assert!(parse_all(&integer::<u8>(), "300").is_err());
+        assert_eq!(Ok("foo_1"), parse_all(&identifier(), "foo_1"));
+        assert_eq!(Err(ParseError::at(0, "Expected an identifier")), parse_all(&identifier(), "1foo"));
+    }
+
+    #[test]
+    fn test_combinators() {
+        let list = literal("[").ignore_then(separated(integer::<u32>(), literal(", "))).then_ignore(literal("]"));
+        assert_eq!(Ok(vec![1, 2, 3]), parse_all(&list, "[1, 2, 3]"));
+        assert_eq!(Ok(vec![]), parse_all(&list, "[]"));
+        assert_eq!(Some(4), parse_all(&list, "[1, x]").unwrap_err().position);
+
+        let command = literal("up").map(|_| -1)
+            .or(literal("down").map(|_| 1))
+            .then_ignore(whitespace())
+            .then(integer::<i32>())
+            .map(|(sign, amount)| sign * amount);
+        assert_eq!(Ok(vec![-3, 5]), parse_lines(&command, "up 3\ndown 5"));
+        assert_eq!(Some(5), parse_lines(&command, "up 3\nleft 5").unwrap_err().position);
+    }
+}
@@ -1,3 +1,105 @@
-pub fn capture_get_usize(capture: &regex::Captures, group: usize) -> usize {
-    capture.get(group).unwrap().as_str().parse().unwrap()
-}
\ No newline at end of file
+use std::{fmt::Display, str::FromStr};
+
+use crate::utils::error::ParseError;
+
+/// Access to the groups of a match, shared by `regex` and `fancy_regex`.
+pub trait CaptureGroups<'h> {
+    fn group(&self, i: usize) -> Option<&'h str>;
+
+    fn group_str(&self, i: usize) -> Result<&'h str, ParseError> {
+        self.group(i).ok_or_else(|| ParseError::new(format!("Capture group {i} did not participate in the match")))
+    }
+
+    fn parse_group<T: FromStr>(&self, i: usize) -> Result<T, ParseError> where T::Err: Display {
+        let text = self.group_str(i)?;
+        text.parse().map_err(|err| ParseError::new(format!("Could not parse group {i} \"{text}\": {err}")))
+    }
+
+    /// Parses the groups starting at 1 into a tuple, e.g. `caps.parse::<(usize, char)>()`.
+    fn parse<T: FromCaptures>(&self) -> Result<T, ParseError> where Self: Sized {
+        T::from_captures(self)
+    }
+}
+
+impl<'h> CaptureGroups<'h> for regex::Captures<'h> {
+    fn group(&self, i: usize) -> Option<&'h str> {
+        self.get(i).map(|m| m.as_str())
+    }
+}
+
+impl<'h> CaptureGroups<'h> for fancy_regex::Captures<'h> {
+    fn group(&self, i: usize) -> Option<&'h str> {
+        self.get(i).map(|m| m.as_str())
+    }
+}
+
+pub trait FromCaptures: Sized {
+    fn from_captures<'h>(captures: &impl CaptureGroups<'h>) -> Result<Self, ParseError>;
+}
+
+macro_rules! impl_from_captures {
+    ($($t:ident $i:tt),+) => {
+        impl<$($t : FromStr),+> FromCaptures for ($($t,)+) where $($t::Err: Display),+ {
+            fn from_captures<'h>(captures: &impl CaptureGroups<'h>) -> Result<Self, ParseError> {
+                Ok(($(captures.parse_group::<$t>($i)?,)+))
+            }
+        }
+    };
+}
+
+impl_from_captures!(A 1);
+impl_from_captures!(A 1, B 2);
+impl_from_captures!(A 1, B 2, C 3);
+impl_from_captures!(A 1, B 2, C 3, D 4);
+impl_from_captures!(A 1, B 2, C 3, D 4, E 5);
+impl_from_captures!(A 1, B 2, C 3, D 4, E 5, F 6);
+
+/// Typed matching of a whole line, shared by `regex` and `fancy_regex`.
+pub trait LinePattern {
+    /// `Ok(None)` when the line does not match, an error when it matches but a group does not parse.
+    fn parse_captures<T: FromCaptures>(&self, line: &str) -> Result<Option<T>, ParseError>;
+
+    fn parse_match<T: FromCaptures>(&self, line: &str) -> Result<T, ParseError> {
+        self.parse_captures(line)?
+            .ok_or_else(|| ParseError::new(format!("Could not parse line \"{line}\"")))
+    }
+}
+
+impl LinePattern for regex::Regex {
+    fn parse_captures<T: FromCaptures>(&self, line: &str) -> Result<Option<T>, ParseError> {
+        self.captures(line).map(|captures| captures.parse()).transpose()
+    }
+}
+
+impl LinePattern for fancy_regex::Regex {
+    fn parse_captures<T: FromCaptures>(&self, line: &str) -> Result<Option<T>, ParseError> {
+        self.captures(line)
+            .map_err(|err| ParseError::new(err.to_string()))?
+            .map(|captures| captures.parse())
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::regex::{CaptureGroups, LinePattern};
+
+    #[test]
+    fn test_parse_captures() {
+        let pattern = regex::Regex::new(r#"(\d+)x(\w)"#).unwrap();
+        assert_eq!(Ok(Some((12_usize, 'a'))), pattern.parse_captures("12xa"));
+        assert_eq!(Ok(None), pattern.parse_captures::<(usize,)>("nothing"));
+        assert!(pattern.parse_captures::<(u8, char)>("300xa").is_err());
+        assert!(pattern.parse_match::<(usize, char)>("nothing").is_err());
+    }
+
+    #[test]
+    fn test_fancy_regex() {
+        let pattern = fancy_regex::Regex::new(r#"(\w+) by (-?\d+)"#).unwrap();
+        assert_eq!(Ok(("row".to_string(), -3_i32)), pattern.parse_match("row by -3"));
+        let captures = pattern.captures("col by 4").unwrap().unwrap();
+        assert_eq!(Ok("col"), captures.group_str(1));
+        assert_eq!(Ok(4_u32), captures.parse_group(2));
+        assert!(captures.group_str(3).is_err());
+    }
+}
@@ -0,0 +1,392 @@
+use std::{cmp::Reverse, collections::{HashMap, HashSet, VecDeque}, hash::Hash, ops::Add};
+
+use priority_queue::PriorityQueue;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+pub struct SearchResult<S, C> {
+    pub cost: C,
+    /// Every state from the start up to and including the goal.
+    pub path: Vec<S>,
+}
+
+/// Finds the cheapest path from `start` to any state satisfying `is_goal`.
+pub fn dijkstra<S, C, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<SearchResult<S, C>>
+where
+    S: Clone + Hash + Eq,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(start, successors, |_| C::default(), is_goal)
+}
+
+/// Finds the cheapest path from `start` to any state satisfying `is_goal`.
+/// The `heuristic` must never overestimate the remaining cost, or the result may not be optimal.
+pub fn astar<S, C, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<SearchResult<S, C>>
+where
+    S: Clone + Hash + Eq,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut open_set = PriorityQueue::new();
+    open_set.push(start.clone(), Reverse(heuristic(&start)));
+
+    // Cheapest known cost to reach each state, and the state it was reached from
+    let mut costs: HashMap<S, (C, Option<S>)> = HashMap::new();
+    costs.insert(start, (C::default(), None));
+    let mut visited = HashSet::new();
+
+    while let Some((state, _)) = open_set.pop() {
+        let cost = costs[&state].0;
+        if is_goal(&state) {
+            let mut path = vec![state];
+            while let Some((_, Some(previous))) = costs.get(path.last().unwrap()) {
+                path.push(previous.clone());
+            }
+            path.reverse();
+            return Some(SearchResult { cost, path });
+        }
+        visited.insert(state.clone());
+
+        for (next, step_cost) in successors(&state) {
+            if visited.contains(&next) { continue; }
+            let new_cost = cost + step_cost;
+            if costs.get(&next).is_none_or(|(old_cost, _)| new_cost < *old_cost) {
+                let priority = Reverse(new_cost + heuristic(&next));
+                costs.insert(next.clone(), (new_cost, Some(state.clone())));
+                open_set.push_increase(next, priority);
+            }
+        }
+    }
+    None
+}
+
+/// The cost of the cheapest path from `start` to any state satisfying `is_goal`.
+/// Unlike [`dijkstra`] this doesn't remember how each state was reached, so it needs far less memory.
+pub fn dijkstra_cost<S, C, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<C>
+where
+    S: Clone + Hash + Eq,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar_cost(start, successors, |_| C::default(), is_goal)
+}
+
+/// The cost of the cheapest path from `start` to any state satisfying `is_goal`, without the path itself.
+/// The `heuristic` must never overestimate the remaining cost, or the result may not be optimal.
+pub fn astar_cost<S, C, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<C>
+where
+    S: Clone + Hash + Eq,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut open_set = PriorityQueue::new();
+    open_set.push(start.clone(), Reverse(heuristic(&start)));
+
+    // Cheapest known cost to reach each state; a state is only re-opened if a cheaper way to it turns up
+    let mut costs: HashMap<S, C> = HashMap::new();
+    costs.insert(start, C::default());
+
+    while let Some((state, _)) = open_set.pop() {
+        let cost = costs[&state];
+        if is_goal(&state) { return Some(cost); }
+
+        for (next, step_cost) in successors(&state) {
+            let new_cost = cost + step_cost;
+            if costs.get(&next).is_none_or(|old_cost| new_cost < *old_cost) {
+                let priority = Reverse(new_cost + heuristic(&next));
+                costs.insert(next.clone(), new_cost);
+                open_set.push_increase(next, priority);
+            }
+        }
+    }
+    None
+}
+
+/// The cheapest cost from `start` to every state reachable from it.
+pub fn dijkstra_all<S, C, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+) -> HashMap<S, C>
+where
+    S: Clone + Hash + Eq,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut open_set = PriorityQueue::new();
+    open_set.push(start.clone(), Reverse(C::default()));
+    let mut costs = HashMap::new();
+
+    while let Some((state, Reverse(cost))) = open_set.pop() {
+        // Settle the state before expanding it, so an edge back to itself can't reopen it
+        costs.insert(state.clone(), cost);
+        for (next, step_cost) in successors(&state) {
+            if costs.contains_key(&next) { continue; }
+            open_set.push_increase(next, Reverse(cost + step_cost));
+        }
+    }
+    costs
+}
+
+/// Breadth-first search from `start`, mapping every state reachable in at most `max_depth` steps
+/// to the number of steps it takes to reach it.
+pub fn reachable_within<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    max_depth: usize,
+) -> HashMap<S, usize>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let mut distances = HashMap::new();
+    distances.insert(start.clone(), 0);
+    let mut open_set = VecDeque::from([(start, 0)]);
+
+    while let Some((state, depth)) = open_set.pop_front() {
+        if depth == max_depth { continue; }
+        for next in successors(&state) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), depth + 1);
+                open_set.push_back((next, depth + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// The number of states reachable from `start` in at most `max_depth` steps, including `start`.
+pub fn count_within<S, I>(start: S, successors: impl FnMut(&S) -> I, max_depth: usize) -> usize
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    reachable_within(start, successors, max_depth).len()
+}
+
+/// All states whose shortest path from `start` takes exactly `depth` steps.
+pub fn at_depth<S, I>(start: S, successors: impl FnMut(&S) -> I, depth: usize) -> Vec<S>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    reachable_within(start, successors, depth).into_iter()
+        .filter(|(_, distance)| *distance == depth)
+        .map(|(state, _)| state)
+        .collect()
+}
+
+/// Summary of all simple paths found by `path_stats`, with lengths counted in steps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PathStats {
+    pub count: usize,
+    pub shortest: Option<usize>,
+    pub longest: Option<usize>,
+}
+
+impl PathStats {
+    fn add(&mut self, length: usize) {
+        self.count += 1;
+        self.shortest = Some(self.shortest.map_or(length, |shortest| shortest.min(length)));
+        self.longest = Some(self.longest.map_or(length, |longest| longest.max(length)));
+    }
+
+    fn merge(self, other: PathStats) -> PathStats {
+        PathStats {
+            count: self.count + other.count,
+            shortest: self.shortest.into_iter().chain(other.shortest).min(),
+            longest: self.longest.into_iter().chain(other.longest).max(),
+        }
+    }
+}
+
+fn walk_paths<S, I>(
+    mut path: Vec<S>,
+    successors: &impl Fn(&S) -> I,
+    is_goal: &impl Fn(&S) -> bool,
+    prune: &impl Fn(&[S]) -> bool,
+    on_path: &mut impl FnMut(&[S]),
+)
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let head = path.last().unwrap();
+    if is_goal(head) {
+        on_path(&path);
+        return;
+    }
+    if prune(&path) { return; }
+
+    let mut visited: HashSet<S> = path.iter().cloned().collect();
+    let mut stack = vec![successors(head).into_iter()];
+    while let Some(branches) = stack.last_mut() {
+        let Some(next) = branches.next() else {
+            stack.pop();
+            if let Some(state) = path.pop() {
+                visited.remove(&state);
+            }
+            continue;
+        };
+        if visited.contains(&next) { continue; }
+        path.push(next);
+        let head = path.last().unwrap();
+        if is_goal(head) {
+            on_path(&path);
+            path.pop();
+        } else if prune(&path) {
+            path.pop();
+        } else {
+            visited.insert(head.clone());
+            stack.push(successors(head).into_iter());
+        }
+    }
+}
+
+/// Calls `on_path` with every simple path from `start` to a goal state.
+/// Paths end at the first goal they reach, and are cut short when `prune` returns true for them.
+pub fn for_each_path<S, I>(
+    start: S,
+    successors: impl Fn(&S) -> I,
+    is_goal: impl Fn(&S) -> bool,
+    prune: impl Fn(&[S]) -> bool,
+    mut on_path: impl FnMut(&[S]),
+)
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    walk_paths(vec![start], &successors, &is_goal, &prune, &mut on_path);
+}
+
+/// Counts all simple paths from `start` to a goal state, along with the shortest and longest one.
+pub fn path_stats<S, I>(
+    start: S,
+    successors: impl Fn(&S) -> I,
+    is_goal: impl Fn(&S) -> bool,
+    prune: impl Fn(&[S]) -> bool,
+) -> PathStats
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let mut stats = PathStats::default();
+    for_each_path(start, successors, is_goal, prune, |path| stats.add(path.len() - 1));
+    stats
+}
+
+/// Same as `path_stats`, but every branch leaving `start` is explored on its own thread.
+pub fn par_path_stats<S, I>(
+    start: S,
+    successors: impl Fn(&S) -> I + Sync,
+    is_goal: impl Fn(&S) -> bool + Sync,
+    prune: impl Fn(&[S]) -> bool + Sync,
+) -> PathStats
+where
+    S: Clone + Hash + Eq + Send + Sync,
+    I: IntoIterator<Item = S>,
+{
+    if is_goal(&start) || prune(std::slice::from_ref(&start)) {
+        return path_stats(start, successors, is_goal, prune);
+    }
+    let branches: Vec<_> = successors(&start).into_iter().filter(|next| *next != start).collect();
+    branches.into_par_iter().map(|next| {
+        let mut stats = PathStats::default();
+        walk_paths(vec![start.clone(), next], &successors, &is_goal, &prune, &mut |path: &[S]| {
+            stats.add(path.len() - 1)
+        });
+        stats
+    }).reduce(PathStats::default, PathStats::merge)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::search::{PathStats, astar, astar_cost, at_depth, count_within, dijkstra, dijkstra_all, dijkstra_cost, par_path_stats, path_stats, reachable_within};
+
+    #[test]
+    fn test_dijkstra() {
+        // 0 -> 1 costs 4, 0 -> 2 -> 1 costs 3
+        let edges = [vec![(1, 4), (2, 1)], vec![(3, 1)], vec![(1, 2)], vec![]];
+        let result = dijkstra(0, |node: &usize| edges[*node].clone(), |node| *node == 3).unwrap();
+        assert_eq!(4, result.cost);
+        assert_eq!(vec![0, 2, 1, 3], result.path);
+        assert!(dijkstra(3, |node: &usize| edges[*node].clone(), |node| *node == 0).is_none());
+        assert_eq!(Some(4), dijkstra_cost(0, |node: &usize| edges[*node].clone(), |node| *node == 3));
+        assert_eq!(None, dijkstra_cost(3, |node: &usize| edges[*node].clone(), |node| *node == 0));
+
+        let costs = dijkstra_all(0, |node: &usize| edges[*node].clone());
+        assert_eq!(vec![0, 3, 1, 4], (0..4).map(|node| costs[&node]).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_dijkstra_all_self_loop() {
+        // Every node has an edge back to itself
+        let edges = [vec![(0, 5), (1, 2)], vec![(1, 1), (2, 3)], vec![(2, 7)]];
+        let costs = dijkstra_all(0, |node: &usize| edges[*node].clone());
+        assert_eq!(vec![0, 2, 5], (0..3).map(|node| costs[&node]).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_astar() {
+        let result = astar(
+            (0_i32, 0_i32),
+            |(x, y)| [((x + 1, *y), 1), ((*x, y + 1), 1), ((x - 1, *y), 1), ((*x, y - 1), 1)],
+            |(x, y)| x.abs_diff(5) + y.abs_diff(-3),
+            |state| *state == (5, -3),
+        ).unwrap();
+        assert_eq!(8, result.cost);
+        assert_eq!(9, result.path.len());
+
+        let cost = astar_cost(
+            (0_i32, 0_i32),
+            |(x, y)| [((x + 1, *y), 1), ((*x, y + 1), 1), ((x - 1, *y), 1), ((*x, y - 1), 1)],
+            |(x, y)| x.abs_diff(5) + y.abs_diff(-3),
+            |state| *state == (5, -3),
+        );
+        assert_eq!(Some(8), cost);
+    }
+
+    #[test]
+    fn test_reachable_within() {
+        let successors = |n: &i32| [n - 1, n + 1];
+        let distances = reachable_within(0, successors, 3);
+        assert_eq!(7, distances.len());
+        assert_eq!(Some(&3), distances.get(&-3));
+        assert_eq!(5, count_within(0, successors, 2));
+        let mut edge = at_depth(0, successors, 2);
+        edge.sort();
+        assert_eq!(vec![-2, 2], edge);
+    }
+
+    #[test]
+    fn test_path_stats() {
+        // Complete graph on 4 nodes, paths from 0 to 3
+        let successors = |n: &usize| {
+            let n = *n;
+            (0..4).filter(move |m| *m != n)
+        };
+        let stats = path_stats(0, successors, |n| *n == 3, |_| false);
+        assert_eq!(PathStats { count: 5, shortest: Some(1), longest: Some(3) }, stats);
+        assert_eq!(stats, par_path_stats(0, successors, |n| *n == 3, |_| false));
+
+        let pruned = path_stats(0, successors, |n| *n == 3, |path| path.len() > 2);
+        assert_eq!(PathStats { count: 3, shortest: Some(1), longest: Some(2) }, pruned);
+    }
+}
@@ -0,0 +1,193 @@
+use std::{collections::HashMap, ops::{Index, IndexMut}};
+
+const DIRECT_OFFSETS: [(i64, i64); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const ALL_OFFSETS: [(i64, i64); 8] = [
+    (0, -1), (1, -1), (1, 0), (1, 1),
+    (0, 1), (-1, 1), (-1, 0), (-1, -1),
+];
+
+/// An unbounded grid that only stores the cells that were set.
+/// Every other cell holds the default value.
+pub struct SparseGrid<T> {
+    default: T,
+    contents: HashMap<(i64, i64), T>,
+    bounds: Option<((i64, i64), (i64, i64))>,
+}
+
+impl<T : Default> SparseGrid<T> {
+    pub fn empty() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new(default: T) -> Self {
+        Self {
+            default,
+            contents: HashMap::new(),
+            bounds: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.contents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contents.is_empty()
+    }
+
+    /// The inclusive `(min, max)` corners of the box containing all set cells.
+    pub fn bounds(&self) -> Option<((i64, i64), (i64, i64))> {
+        self.bounds
+    }
+
+    pub fn in_bounds(&self, x: i64, y: i64) -> bool {
+        match self.bounds {
+            Some(((min_x, min_y), (max_x, max_y))) => {
+                (min_x..=max_x).contains(&x) && (min_y..=max_y).contains(&y)
+            },
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        self.contents.contains_key(&(x, y))
+    }
+
+    /// The value at `(x, y)`, or `None` when the cell was never set.
+    pub fn get(&self, x: i64, y: i64) -> Option<&T> {
+        self.contents.get(&(x, y))
+    }
+
+    /// The value at `(x, y)`, falling back to the default value.
+    pub fn get_or_default(&self, x: i64, y: i64) -> &T {
+        self.contents.get(&(x, y)).unwrap_or(&self.default)
+    }
+
+    pub fn get_mut(&mut self, x: i64, y: i64) -> Option<&mut T> {
+        self.contents.get_mut(&(x, y))
+    }
+
+    fn grow_bounds(&mut self, x: i64, y: i64) {
+        self.bounds = Some(match self.bounds {
+            Some(((min_x, min_y), (max_x, max_y))) => {
+                ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y)))
+            },
+            None => ((x, y), (x, y)),
+        });
+    }
+
+    pub fn insert(&mut self, x: i64, y: i64, value: T) -> Option<T> {
+        self.grow_bounds(x, y);
+        self.contents.insert((x, y), value)
+    }
+
+    /// Clears the cell at `(x, y)` back to the default value.
+    pub fn remove(&mut self, x: i64, y: i64) -> Option<T> {
+        let removed = self.contents.remove(&(x, y))?;
+        if let Some(((min_x, min_y), (max_x, max_y))) = self.bounds
+            && (x == min_x || x == max_x || y == min_y || y == max_y) {
+            self.bounds = None;
+            let coords: Vec<_> = self.contents.keys().copied().collect();
+            for (x, y) in coords {
+                self.grow_bounds(x, y);
+            }
+        }
+        Some(removed)
+    }
+
+    /// The 4 orthogonal neighbours of `(x, y)`, in the order up, right, down, left.
+    pub fn direct_neighbours(&self, x: i64, y: i64) -> impl Iterator<Item=(i64, i64, &T)> {
+        DIRECT_OFFSETS.iter().map(move |(dx, dy)| {
+            (x + dx, y + dy, self.get_or_default(x + dx, y + dy))
+        })
+    }
+
+    /// The 8 neighbours of `(x, y)`, clockwise starting from up.
+    pub fn all_neighbours(&self, x: i64, y: i64) -> impl Iterator<Item=(i64, i64, &T)> {
+        ALL_OFFSETS.iter().map(move |(dx, dy)| {
+            (x + dx, y + dy, self.get_or_default(x + dx, y + dy))
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&T> {
+        self.contents.values()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item=&mut T> {
+        self.contents.values_mut()
+    }
+
+    /// Iterates over all set cells as `(x, y, value)`, in no particular order.
+    pub fn iter_coords(&self) -> impl Iterator<Item=(i64, i64, &T)> {
+        self.contents.iter().map(|((x, y), value)| (*x, *y, value))
+    }
+
+    /// Draws the bounding box of the set cells, one line per row with `y` increasing downwards.
+    pub fn render(&self, to_char: impl Fn(&T) -> char) -> String {
+        let Some(((min_x, min_y), (max_x, max_y))) = self.bounds else {
+            return String::new();
+        };
+        (min_y..=max_y).map(|y| {
+            (min_x..=max_x).map(|x| to_char(self.get_or_default(x, y))).collect::<String>()
+        }).collect::<Vec<_>>().join("\n")
+    }
+}
+
+impl<T : Clone> SparseGrid<T> {
+    /// Replaces the value at `(x, y)`, returning the previous one.
+    pub fn set(&mut self, x: i64, y: i64, value: T) -> T {
+        self.insert(x, y, value).unwrap_or_else(|| self.default.clone())
+    }
+}
+
+impl<T> Index<(i64, i64)> for SparseGrid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (i64, i64)) -> &Self::Output {
+        self.get_or_default(x, y)
+    }
+}
+
+impl<T : Clone> IndexMut<(i64, i64)> for SparseGrid<T> {
+    fn index_mut(&mut self, (x, y): (i64, i64)) -> &mut Self::Output {
+        self.grow_bounds(x, y);
+        self.contents.entry((x, y)).or_insert_with(|| self.default.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::sparse_grid::SparseGrid;
+
+    #[test]
+    fn test_bounds() {
+        let mut grid: SparseGrid<bool> = SparseGrid::empty();
+        assert_eq!(None, grid.bounds());
+        grid.set(-2, 3, true);
+        grid[(4, -1)] = true;
+        assert_eq!(Some(((-2, -1), (4, 3))), grid.bounds());
+        assert!(grid.in_bounds(0, 0));
+        assert!(!grid[(0, 0)]);
+        grid.remove(4, -1);
+        assert_eq!(Some(((-2, 3), (-2, 3))), grid.bounds());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let mut grid = SparseGrid::new(0);
+        grid.set(0, -1, 5);
+        let values: Vec<_> = grid.direct_neighbours(0, 0).map(|(_, _, v)| *v).collect();
+        assert_eq!(vec![5, 0, 0, 0], values);
+        assert_eq!(8, grid.all_neighbours(-100, 100).count());
+    }
+
+    #[test]
+    fn test_render() {
+        let mut grid: SparseGrid<bool> = SparseGrid::empty();
+        grid.set(-1, -1, true);
+        grid.set(1, 0, true);
+        assert_eq!("#..\n..#", grid.render(|v| if *v {'#'} else {'.'}));
+    }
+}
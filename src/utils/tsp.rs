@@ -0,0 +1,119 @@
+/// A route through every point of a distance matrix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub cost: u32,
+    /// The points in visiting order, starting with the start point.
+    /// For a closed tour the start point is repeated at the end.
+    pub order: Vec<usize>,
+}
+
+/// The shortest route starting at `start` that visits every point, ending anywhere.
+/// `distances[a][b]` is the distance from `a` to `b`, with `u32::MAX` for unreachable pairs.
+pub fn shortest_path(distances: &[Vec<u32>], start: usize) -> Option<Route> {
+    held_karp(distances, start, false)
+}
+
+/// The shortest route starting at `start` that visits every point and returns to `start`.
+pub fn shortest_tour(distances: &[Vec<u32>], start: usize) -> Option<Route> {
+    held_karp(distances, start, true)
+}
+
+fn step(distances: &[Vec<u32>], from: usize, to: usize, cost: u32) -> Option<u32> {
+    match distances[from][to] {
+        u32::MAX => None,
+        distance => cost.checked_add(distance),
+    }
+}
+
+fn held_karp(distances: &[Vec<u32>], start: usize, closed: bool) -> Option<Route> {
+    let others: Vec<_> = (0..distances.len()).filter(|point| *point != start).collect();
+    let count = others.len();
+    if count == 0 {
+        let order = if closed { vec![start, start] } else { vec![start] };
+        return Some(Route { cost: 0, order });
+    }
+
+    // costs[visited * count + last] is the cheapest way to visit the points in the `visited`
+    // bitmask, ending at `last`. parents holds the point visited right before `last`.
+    let subsets = 1_usize << count;
+    let mut costs = vec![u32::MAX; subsets * count];
+    let mut parents = vec![usize::MAX; subsets * count];
+    for (i, point) in others.iter().enumerate() {
+        if let Some(cost) = step(distances, start, *point, 0) {
+            costs[(1 << i) * count + i] = cost;
+        }
+    }
+
+    for visited in 1..subsets {
+        for last in 0..count {
+            let cost = costs[visited * count + last];
+            if cost == u32::MAX { continue; }
+            for next in (0..count).filter(|next| visited & (1 << next) == 0) {
+                let Some(new_cost) = step(distances, others[last], others[next], cost) else { continue };
+                let index = (visited | (1 << next)) * count + next;
+                if new_cost < costs[index] {
+                    costs[index] = new_cost;
+                    parents[index] = last;
+                }
+            }
+        }
+    }
+
+    let all = subsets - 1;
+    let (cost, mut last) = (0..count).filter_map(|last| {
+        let cost = costs[all * count + last];
+        if cost == u32::MAX { return None; }
+        let total = if closed { step(distances, others[last], start, cost)? } else { cost };
+        Some((total, last))
+    }).min()?;
+
+    let mut order = Vec::with_capacity(count + 2);
+    if closed {
+        order.push(start);
+    }
+    let mut visited = all;
+    while last != usize::MAX {
+        order.push(others[last]);
+        let parent = parents[visited * count + last];
+        visited &= !(1 << last);
+        last = parent;
+    }
+    order.push(start);
+    order.reverse();
+    Some(Route { cost, order })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::tsp::{Route, shortest_path, shortest_tour};
+
+    fn square() -> Vec<Vec<u32>> {
+        // Points on the corners of a 1 by 3 rectangle, in the order 0, 2, 1, 3 around it
+        vec![
+            vec![0, 4, 1, 3],
+            vec![4, 0, 3, 1],
+            vec![1, 3, 0, 4],
+            vec![3, 1, 4, 0],
+        ]
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        assert_eq!(Some(Route { cost: 5, order: vec![0, 2, 1, 3] }), shortest_path(&square(), 0));
+    }
+
+    #[test]
+    fn test_shortest_tour() {
+        let route = shortest_tour(&square(), 1).unwrap();
+        assert_eq!(8, route.cost);
+        assert_eq!(5, route.order.len());
+        assert_eq!((Some(&1), Some(&1)), (route.order.first(), route.order.last()));
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let distances = vec![vec![0, u32::MAX], vec![u32::MAX, 0]];
+        assert_eq!(None, shortest_path(&distances, 0));
+        assert_eq!(Some(Route { cost: 0, order: vec![0] }), shortest_path(&[vec![0]], 0));
+    }
+}